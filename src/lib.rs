@@ -1,8 +1,20 @@
-use rand::seq::IteratorRandom;
-use rand::SeedableRng;
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+use alloc::collections::btree_map::Entry;
+use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, VecDeque};
+use alloc::string::String;
+use alloc::vec::Vec;
+use alloc::{format, vec};
+use core::cmp::{Ordering, Reverse};
+use core::fmt;
+use core::ops::{Index, IndexMut};
+use rand::seq::{IteratorRandom, SliceRandom};
+use rand::{Rng, SeedableRng};
 use rand_pcg::Pcg64;
-use std::collections::HashSet;
-use std::fmt;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
 
 #[derive(Eq, PartialEq, Debug, Copy, Clone)]
 pub struct Size {
@@ -10,9 +22,30 @@ pub struct Size {
     pub height: usize,
 }
 
+/// Axis-aligned block of cells: `width` columns from `x` and `height` rows from `y`.
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub fn contains(&self, coord: Coord) -> bool {
+        (self.x..self.x + self.width).contains(&coord.0)
+            && (self.y..self.y + self.height).contains(&coord.1)
+    }
+}
+
 type Coord = (usize, usize);
+type Coord3D = (usize, usize, usize);
+// axial (q, r), with the center cell at (0, 0)
+type HexCoord = (isize, isize);
+// (ring, index in the ring), index 0 at the top and going clockwise
+type ThetaCoord = (usize, usize);
 
-#[derive(Eq, PartialEq, Debug, Hash, Copy, Clone)]
+#[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Hash, Copy, Clone)]
 pub enum TileDirection {
     NORTH,
     EAST,
@@ -27,77 +60,456 @@ pub const ALL_TILE_DIRECTIONS: [&TileDirection; 4] = [
     &TileDirection::WEST,
 ];
 
-pub struct Cell {
+/// Summary returned by `Maze::stats`, computed from the open passages with outer walls
+/// counting as closed.
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub struct MazeStats {
+    /// Cells with a single passage.
+    pub dead_ends: usize,
+    /// Cells with exactly two passages.
+    pub corridors: usize,
+    /// Cells with three passages or more.
+    pub junctions: usize,
+    /// Cells in the longest run of connected corridor cells.
+    pub longest_corridor: usize,
+    pub passage_count: usize,
+}
+
+/// Layout of a rendered grid: every cell is `cell_size` wide, enclosed by walls of
+/// `wall_thickness` and spaced from its neighbors by `cell_margin` on each side.
+#[derive(PartialEq, Debug, Copy, Clone)]
+pub struct RenderConfig {
+    pub wall_thickness: f64,
+    pub cell_size: f64,
+    pub cell_margin: f64,
+}
+
+impl Default for RenderConfig {
+    fn default() -> Self {
+        Self {
+            wall_thickness: 1.0,
+            cell_size: 25.0,
+            cell_margin: 2.0,
+        }
+    }
+}
+
+impl RenderConfig {
+    /// Distance between the origins of two adjacent cells.
+    pub fn cell_full_size(&self) -> f64 {
+        (self.wall_thickness + self.cell_margin) * 2.0 + self.cell_size
+    }
+
+    /// Top-left corner of cell (x, y).
+    pub fn cell_origin(&self, coord: Coord) -> (f64, f64) {
+        let full_size = self.cell_full_size();
+        (full_size * coord.0 as f64, full_size * coord.1 as f64)
+    }
+
+    /// `[width, height]` needed to draw a whole maze of the given size.
+    pub fn window_size(&self, size: &Size) -> [f64; 2] {
+        [
+            size.width as f64 * self.cell_full_size(),
+            size.height as f64 * self.cell_full_size(),
+        ]
+    }
+}
+
+/// Extra elements drawn by `Maze::to_svg_with`, all off by default.
+#[derive(Eq, PartialEq, Debug, Copy, Clone, Default)]
+pub struct SvgOptions {
+    /// Writes "x,y" in the middle of every cell.
+    pub label_cells: bool,
+}
+
+/// Generation algorithms available through `MazeBuilder`.
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub enum Algorithm {
+    Backtracker,
+    Prim,
+    Kruskal,
+    Wilson,
+    AldousBroder,
+    BinaryTree,
+    Sidewinder,
+    Eller,
+    RecursiveDivision,
+    HuntAndKill,
+    GrowingTree(CellSelection),
+}
+
+/// One move of a recorded generation run.
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub enum GenEvent {
+    /// The wall between both cells got carved.
+    Carve { from: Coord, to: Coord },
+    /// Dead end, the generator stepped back to `to`.
+    Backtrack { to: Coord },
+    /// The generator reached a new cell.
+    Visit { coord: Coord },
+}
+
+/// Sides of a pointy-topped hexagon.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Hash, Copy, Clone)]
+pub enum HexDirection {
+    NE,
+    E,
+    SE,
+    SW,
+    W,
+    NW,
+}
+
+pub const ALL_HEX_DIRECTIONS: [&HexDirection; 6] = [
+    &HexDirection::NE,
+    &HexDirection::E,
+    &HexDirection::SE,
+    &HexDirection::SW,
+    &HexDirection::W,
+    &HexDirection::NW,
+];
+
+impl HexDirection {
+    fn offset(&self) -> HexCoord {
+        match self {
+            HexDirection::NE => (1, -1),
+            HexDirection::E => (1, 0),
+            HexDirection::SE => (0, 1),
+            HexDirection::SW => (-1, 1),
+            HexDirection::W => (-1, 0),
+            HexDirection::NW => (0, -1),
+        }
+    }
+
+    fn opposite(&self) -> HexDirection {
+        match self {
+            HexDirection::NE => HexDirection::SW,
+            HexDirection::E => HexDirection::W,
+            HexDirection::SE => HexDirection::NW,
+            HexDirection::SW => HexDirection::NE,
+            HexDirection::W => HexDirection::E,
+            HexDirection::NW => HexDirection::SE,
+        }
+    }
+}
+
+/// Which active cell the growing-tree algorithm carves from next.
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub enum CellSelection {
+    /// Most recently added, same as the backtracker.
+    Newest,
+    /// Any active cell, similar to Prim's.
+    Random,
+    /// Least recently added.
+    Oldest,
+}
+
+#[derive(Clone)]
+pub struct Cell<T = ()> {
     pub coord: Coord,
-    walls: HashSet<TileDirection>,
+    // one bit per direction, in `ALL_TILE_DIRECTIONS` order
+    walls: u8,
+    pub data: T,
 }
 
 impl Cell {
     pub fn new(coord: Coord) -> Self {
+        Cell::new_with_data(coord, ())
+    }
+}
+
+impl<T> Cell<T> {
+    pub fn new_with_data(coord: Coord, data: T) -> Self {
         Self {
             coord,
-            walls: HashSet::new(),
+            walls: 0,
+            data,
+        }
+    }
+
+    fn wall_bit(dir: &TileDirection) -> u8 {
+        match dir {
+            TileDirection::NORTH => 1,
+            TileDirection::EAST => 1 << 1,
+            TileDirection::SOUTH => 1 << 2,
+            TileDirection::WEST => 1 << 3,
         }
     }
 
     pub fn enable_wall(&mut self, dir: &TileDirection) {
-        self.walls.insert(*dir);
+        self.walls |= Self::wall_bit(dir);
     }
 
     pub fn disable_wall(&mut self, dir: &TileDirection) {
-        self.walls.remove(dir);
+        self.walls &= !Self::wall_bit(dir);
     }
 
     pub fn is_wall_enabled(&self, dir: &TileDirection) -> bool {
-        self.walls.contains(dir)
+        self.walls & Self::wall_bit(dir) != 0
+    }
+
+    /// Sides without a wall. Only the cell's own walls are counted, see
+    /// `Maze::passage_degree` for the number of reachable neighbors.
+    pub fn open_wall_count(&self) -> usize {
+        4 - self.walls.count_ones() as usize
     }
 }
 
-pub struct Maze {
+/// How the grid edges behave.
+#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+pub enum Topology {
+    /// Sealed outer border.
+    Planar,
+    /// Opposite edges are connected, like a Pac-Man world.
+    Toroidal,
+}
+
+#[derive(Clone)]
+pub struct Maze<T = ()> {
     pub size: Size,
-    cells: Vec<Vec<Cell>>,
+    pub topology: Topology,
+    pub entrance: Coord,
+    pub exit: Coord,
+    // outer walls carved open at the entrance and exit
+    entrance_dir: TileDirection,
+    exit_dir: TileDirection,
+    // false when outer walls are plain walls that can be carved, for tiling
+    sealed_border: bool,
+    cells: Vec<Vec<Cell<T>>>,
 }
 
 impl Maze {
     pub fn new(size: &Size) -> Self {
-        let mut cells = Vec::new();
+        Maze::new_with_topology(size, Topology::Planar)
+    }
 
-        for i in 0..size.width {
-            let mut row = Vec::new();
-            for j in 0..size.height {
-                let mut cell = Cell::new((i, j));
-                // west edge
-                if i == 0 {
-                    cell.enable_wall(&TileDirection::WEST);
+    /// Like `new`, but rejects sizes with a zero width or height instead of panicking.
+    pub fn try_new(size: &Size) -> Result<Self, MazeError> {
+        if size.width == 0 || size.height == 0 {
+            return Err(MazeError::ZeroDimension);
+        }
+
+        Ok(Maze::new(size))
+    }
+
+    pub fn new_with_topology(size: &Size, topology: Topology) -> Self {
+        Maze::new_with_data(size, topology, |_| ())
+    }
+
+    /// With `sealed` false the outer walls become ordinary walls that start open and
+    /// can be enabled or disabled one by one, so that tiles placed edge to edge connect.
+    /// Generators leave them as they are: a generated unsealed maze is open all around.
+    pub fn new_with_border(size: &Size, sealed: bool) -> Self {
+        let mut maze = Maze::new(size);
+        if !sealed {
+            maze.unseal_border();
+        }
+        maze
+    }
+
+    /// Reads back the grid produced by `to_wall_grid`. Open positions on the outer
+    /// border become the entrance and exit, in row order; a single one is used for both.
+    pub fn from_wall_grid(grid: &[Vec<bool>]) -> Result<Maze, MazeError> {
+        let grid_height = grid.first().map_or(0, |column| column.len());
+        if grid.len() < 3
+            || grid_height < 3
+            || grid.len().is_multiple_of(2)
+            || grid_height.is_multiple_of(2)
+            || grid.iter().any(|column| column.len() != grid_height)
+        {
+            return Err(MazeError::BadGridDimensions);
+        }
+
+        let size = Size {
+            width: grid.len() / 2,
+            height: grid_height / 2,
+        };
+        let mut maze = Maze::new(&size);
+        maze.enable_all_walls();
+
+        let mut openings = Vec::new();
+        for j in 0..size.height {
+            for i in 0..size.width {
+                let (x, y) = (2 * i + 1, 2 * j + 1);
+                let walls = [
+                    (TileDirection::NORTH, grid[x][y - 1]),
+                    (TileDirection::EAST, grid[x + 1][y]),
+                    (TileDirection::SOUTH, grid[x][y + 1]),
+                    (TileDirection::WEST, grid[x - 1][y]),
+                ];
+                for (dir, is_wall) in walls.iter() {
+                    if *is_wall {
+                        continue;
+                    }
+                    if maze.is_edge_wall((i, j), dir) {
+                        openings.push(((i, j), *dir));
+                    } else {
+                        maze.disable_wall((i, j), dir);
+                    }
                 }
-                // east edge
-                if i == size.height - 1 {
-                    cell.enable_wall(&TileDirection::EAST);
+            }
+        }
+
+        match openings[..] {
+            [(coord, dir)] => {
+                maze.set_entrance(coord, &dir);
+                maze.set_exit(coord, &dir);
+            }
+            [(entrance, entrance_dir), (exit, exit_dir)] => {
+                maze.set_entrance(entrance, &entrance_dir);
+                maze.set_exit(exit, &exit_dir);
+            }
+            _ => return Err(MazeError::BadOpenings),
+        }
+
+        Ok(maze)
+    }
+
+    /// Reads back the format produced by `Display`. Cells marked with `*` (as in
+    /// `render_with_path`) are accepted as open cells.
+    pub fn from_ascii(s: &str) -> Result<Maze, ParseError> {
+        let lines: Vec<Vec<char>> = s.lines().map(|line| line.chars().collect()).collect();
+
+        // " ___..." with 2 * width - 1 underscores
+        let header = lines.first().ok_or(ParseError::Empty)?;
+        if header.len() < 2
+            || !header.len().is_multiple_of(2)
+            || header[0] != ' '
+            || header[1..].iter().any(|c| *c != '_')
+        {
+            return Err(ParseError::BadHeader);
+        }
+
+        // one line per row plus one between each pair of rows
+        if !lines.len().is_multiple_of(2) {
+            return Err(ParseError::MissingLine);
+        }
+
+        let size = Size {
+            width: header.len() / 2,
+            height: lines.len() / 2,
+        };
+        let mut maze = Maze::new(&size);
+        maze.enable_all_walls();
+
+        let expected_length = 2 * size.width + 1;
+        for (line_index, line) in lines.iter().enumerate().skip(1) {
+            if line.len() != expected_length {
+                return Err(ParseError::RaggedLine {
+                    line: line_index,
+                    expected: expected_length,
+                    found: line.len(),
+                });
+            }
+
+            // even lines hold cells and east walls, odd lines south walls and posts
+            let j = (line_index - 1) / 2;
+            let is_row_line = !line_index.is_multiple_of(2);
+
+            for (column, c) in line.iter().enumerate() {
+                let unexpected = ParseError::UnexpectedChar {
+                    line: line_index,
+                    column,
+                    found: *c,
+                };
+
+                if column == 0 || column == expected_length - 1 {
+                    if *c != '|' {
+                        return Err(unexpected);
+                    }
+                    continue;
                 }
-                // north edge
-                if j == 0 {
-                    cell.enable_wall(&TileDirection::NORTH);
+
+                let i = (column - 1) / 2;
+                let on_cell_column = !column.is_multiple_of(2);
+                match (is_row_line, on_cell_column, *c) {
+                    (true, true, ' ') | (true, true, '*') => {}
+                    (true, false, '#') | (false, true, '#') | (false, false, '#') => {}
+                    (true, false, ' ') => maze.disable_wall((i, j), &TileDirection::EAST),
+                    (false, true, ' ') => maze.disable_wall((i, j), &TileDirection::SOUTH),
+                    _ => return Err(unexpected),
                 }
-                // south edge
-                if i == size.height - 1 {
-                    cell.enable_wall(&TileDirection::SOUTH);
+            }
+        }
+
+        Ok(maze)
+    }
+}
+
+impl<T> Maze<T> {
+    /// Like `new_with_topology`, with the payload of every cell given by `data`.
+    pub fn new_with_data(
+        size: &Size,
+        topology: Topology,
+        mut data: impl FnMut(Coord) -> T,
+    ) -> Self {
+        if size.width == 0 || size.height == 0 {
+            panic!("{}", MazeError::ZeroDimension);
+        }
+
+        // top-left opening north, bottom-right opening south
+        let mut maze = Self {
+            size: *size,
+            topology,
+            entrance: (0, 0),
+            exit: (size.width - 1, size.height - 1),
+            entrance_dir: TileDirection::NORTH,
+            exit_dir: TileDirection::SOUTH,
+            sealed_border: true,
+            cells: Vec::new(),
+        };
+
+        for i in 0..size.width {
+            let mut row = Vec::new();
+            for j in 0..size.height {
+                let mut cell = Cell::new_with_data((i, j), data((i, j)));
+                for dir in ALL_TILE_DIRECTIONS.iter() {
+                    if maze.is_edge_wall((i, j), dir) && !maze.is_opening((i, j), dir) {
+                        cell.enable_wall(dir);
+                    }
                 }
                 row.push(cell);
             }
-            cells.push(row);
+            maze.cells.push(row);
         }
 
-        Self { size: *size, cells }
+        maze
+    }
+
+    // a single row or column can't wrap onto itself
+    fn wraps_horizontally(&self) -> bool {
+        self.topology == Topology::Toroidal && self.size.width > 1
     }
 
-    pub fn get_cell(&self, coord: Coord) -> Option<&Cell> {
+    fn wraps_vertically(&self) -> bool {
+        self.topology == Topology::Toroidal && self.size.height > 1
+    }
+
+    pub fn get_cell(&self, coord: Coord) -> Option<&Cell<T>> {
         self.cells.get(coord.0)?.get(coord.1)
     }
 
-    pub fn get_mut_cell(&mut self, coord: Coord) -> Option<&mut Cell> {
+    pub fn get_mut_cell(&mut self, coord: Coord) -> Option<&mut Cell<T>> {
         self.cells.get_mut(coord.0)?.get_mut(coord.1)
     }
 
+    pub fn cell_data(&self, coord: Coord) -> Option<&T> {
+        self.get_cell(coord).map(|cell| &cell.data)
+    }
+
+    pub fn cell_data_mut(&mut self, coord: Coord) -> Option<&mut T> {
+        self.get_mut_cell(coord).map(|cell| &mut cell.data)
+    }
+
+    /// All cells in storage order: column by column, top to bottom.
+    pub fn iter_cells(&self) -> impl Iterator<Item = &Cell<T>> {
+        self.cells.iter().flatten()
+    }
+
+    pub fn iter_cells_mut(&mut self) -> impl Iterator<Item = &mut Cell<T>> {
+        self.cells.iter_mut().flatten()
+    }
+
     fn get_neighbor_coords_and_dirs(&self, coord: Coord) -> Vec<(Coord, TileDirection)> {
         let mut all_neighbors = vec![
             ((coord.0 + 1, coord.1), TileDirection::EAST),
@@ -112,16 +524,41 @@ impl Maze {
             all_neighbors.push(((coord.0, coord.1 - 1), TileDirection::NORTH));
         }
 
+        // across the seams
+        if self.wraps_horizontally() {
+            if coord.0 == self.size.width - 1 {
+                all_neighbors.push(((0, coord.1), TileDirection::EAST));
+            }
+            if coord.0 == 0 {
+                all_neighbors.push(((self.size.width - 1, coord.1), TileDirection::WEST));
+            }
+        }
+        if self.wraps_vertically() {
+            if coord.1 == self.size.height - 1 {
+                all_neighbors.push(((coord.0, 0), TileDirection::SOUTH));
+            }
+            if coord.1 == 0 {
+                all_neighbors.push(((coord.0, self.size.height - 1), TileDirection::NORTH));
+            }
+        }
+
         all_neighbors
             .into_iter()
             .filter(|(coord, _)| self.is_valid_coord(coord))
             .collect()
     }
 
-    pub fn get_neighbor_cells_and_dir(&self, coord: Coord) -> Vec<(&Cell, TileDirection)> {
+    /// Neighbors in a fixed NORTH, EAST, SOUTH, WEST order, across the seams on a torus.
+    pub fn neighbors_ordered(&self, coord: Coord) -> Vec<(Coord, TileDirection)> {
+        let mut neighbors = self.get_neighbor_coords_and_dirs(coord);
+        neighbors.sort_by_key(|(_, dir)| ALL_TILE_DIRECTIONS.iter().position(|d| *d == dir));
+        neighbors
+    }
+
+    pub fn get_neighbor_cells_and_dir(&self, coord: Coord) -> Vec<(&Cell<T>, TileDirection)> {
         self.get_neighbor_coords_and_dirs(coord)
             .into_iter()
-            .map(|(coord, dir)| (self.get_cell(coord).unwrap(), dir))
+            .map(|(coord, dir)| (&self[coord], dir))
             .collect()
     }
 
@@ -134,11 +571,27 @@ impl Maze {
         }
     }
 
+    fn get_clockwise(direction: &TileDirection) -> TileDirection {
+        match direction {
+            TileDirection::NORTH => TileDirection::EAST,
+            TileDirection::EAST => TileDirection::SOUTH,
+            TileDirection::SOUTH => TileDirection::WEST,
+            TileDirection::WEST => TileDirection::NORTH,
+        }
+    }
+
+    fn get_dir_between(&self, from: Coord, to: Coord) -> Option<TileDirection> {
+        self.get_neighbor_coords_and_dirs(from)
+            .into_iter()
+            .find(|(coord, _)| *coord == to)
+            .map(|(_, dir)| dir)
+    }
+
     fn get_mut_neighbor_cell_and_shared_wall(
         &mut self,
         coord: Coord,
         direction: &TileDirection,
-    ) -> Option<(&mut Cell, TileDirection)> {
+    ) -> Option<(&mut Cell<T>, TileDirection)> {
         let (coord, dir) = self
             .get_neighbor_coords_and_dirs(coord)
             .into_iter()
@@ -146,51 +599,186 @@ impl Maze {
 
         match self.get_mut_cell(coord) {
             None => None,
-            Some(cell) => Some((cell, Maze::get_opposite(&dir))),
+            Some(cell) => Some((cell, Self::get_opposite(&dir))),
         }
     }
 
     fn is_edge_wall(&self, coord: Coord, direction: &TileDirection) -> bool {
         match direction {
-            TileDirection::NORTH => coord.1 == 0,
-            TileDirection::EAST => coord.0 == self.size.width - 1,
-            TileDirection::SOUTH => coord.1 == self.size.height - 1,
-            TileDirection::WEST => coord.0 == 0,
+            TileDirection::NORTH => coord.1 == 0 && !self.wraps_vertically(),
+            TileDirection::EAST => coord.0 == self.size.width - 1 && !self.wraps_horizontally(),
+            TileDirection::SOUTH => coord.1 == self.size.height - 1 && !self.wraps_vertically(),
+            TileDirection::WEST => coord.0 == 0 && !self.wraps_horizontally(),
+        }
+    }
+
+    fn is_opening(&self, coord: Coord, direction: &TileDirection) -> bool {
+        (coord == self.entrance && *direction == self.entrance_dir)
+            || (coord == self.exit && *direction == self.exit_dir)
+    }
+
+    // moves an opening: closes the outer wall it used to carve and carves the new one
+    fn move_opening(&mut self, is_entrance: bool, coord: Coord, direction: &TileDirection) {
+        assert!(
+            self.is_valid_coord(&coord) && self.is_edge_wall(coord, direction),
+            "openings must be carved in an outer wall"
+        );
+
+        let (old_coord, old_dir) = if is_entrance {
+            (self.entrance, self.entrance_dir)
+        } else {
+            (self.exit, self.exit_dir)
+        };
+        if is_entrance {
+            self.entrance = coord;
+            self.entrance_dir = *direction;
+        } else {
+            self.exit = coord;
+            self.exit_dir = *direction;
+        }
+
+        // unsealed borders keep the old opening carved, like any other wall
+        if self.sealed_border
+            && self.is_edge_wall(old_coord, &old_dir)
+            && !self.is_opening(old_coord, &old_dir)
+        {
+            self[old_coord].enable_wall(&old_dir);
+        }
+        self[coord].disable_wall(direction);
+    }
+
+    /// Carves the outer wall of `coord` in `direction` as the entrance, closing the
+    /// previous one. Panics if that wall isn't on the outer border.
+    pub fn set_entrance(&mut self, coord: Coord, direction: &TileDirection) {
+        self.move_opening(true, coord, direction);
+    }
+
+    /// Same as `set_entrance`, for the exit.
+    pub fn set_exit(&mut self, coord: Coord, direction: &TileDirection) {
+        self.move_opening(false, coord, direction);
+    }
+
+    /// Moves the entrance to a random cell of the top border and the exit to a random
+    /// cell of the bottom border. If they aren't connected yet, passages are carved down
+    /// the entrance column and along the bottom row, only between cells that don't reach
+    /// each other so a perfect maze stays perfect. Panics if the maze wraps vertically.
+    pub fn place_opposite_entrances(&mut self, rng_seed: u64) {
+        let mut rng = Pcg64::seed_from_u64(rng_seed);
+        let (width, height) = (self.size.width, self.size.height);
+
+        let entrance = (rng.gen_range(0..width), 0);
+        let exit = (rng.gen_range(0..width), height - 1);
+        self.set_entrance(entrance, &TileDirection::NORTH);
+        self.set_exit(exit, &TileDirection::SOUTH);
+
+        let column = (0..height).map(|y| (entrance.0, y));
+        let row: Vec<Coord> = if exit.0 < entrance.0 {
+            (exit.0..entrance.0)
+                .rev()
+                .map(|x| (x, height - 1))
+                .collect()
+        } else {
+            (entrance.0 + 1..=exit.0).map(|x| (x, height - 1)).collect()
+        };
+        let route: Vec<Coord> = column.chain(row).collect();
+        for step in route.windows(2) {
+            if !self.is_reachable(step[0], step[1]) {
+                self.connect(step[0], step[1]).unwrap();
+            }
+        }
+    }
+
+    pub fn is_border_sealed(&self) -> bool {
+        self.sealed_border
+    }
+
+    // opens every outer wall and lets them be toggled like the others
+    fn unseal_border(&mut self) {
+        self.sealed_border = false;
+        for i in 0..self.size.width {
+            for j in 0..self.size.height {
+                for dir in ALL_TILE_DIRECTIONS.iter() {
+                    if self.is_edge_wall((i, j), dir) {
+                        self[(i, j)].disable_wall(dir);
+                    }
+                }
+            }
         }
     }
 
     pub fn is_wall_enabled(&self, coord: Coord, direction: &TileDirection) -> bool {
-        if self.is_edge_wall(coord, direction) {
-            return true;
+        if self.sealed_border && self.is_edge_wall(coord, direction) {
+            return !self.is_opening(coord, direction);
         }
 
-        self.get_cell(coord).unwrap().is_wall_enabled(direction)
+        self[coord].is_wall_enabled(direction)
     }
 
+    /// Panics if `coord` is out of bounds, see `try_enable_wall`.
     pub fn enable_wall(&mut self, coord: Coord, direction: &TileDirection) {
-        if self.is_edge_wall(coord, direction) {
-            return;
+        if let Err(err) = self.try_enable_wall(coord, direction) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Panics if `coord` is out of bounds, see `try_disable_wall`.
+    pub fn disable_wall(&mut self, coord: Coord, direction: &TileDirection) {
+        if let Err(err) = self.try_disable_wall(coord, direction) {
+            panic!("{}", err);
         }
+    }
 
-        self.get_mut_cell(coord).unwrap().enable_wall(direction);
+    /// Enables the wall on both cells sharing it; outer walls are left untouched.
+    pub fn try_enable_wall(
+        &mut self,
+        coord: Coord,
+        direction: &TileDirection,
+    ) -> Result<(), MazeError> {
+        self.set_wall(coord, direction, true)
+    }
 
-        let (neighbor_cell, shared_wall_dir) = self
-            .get_mut_neighbor_cell_and_shared_wall(coord, direction)
-            .unwrap();
-        neighbor_cell.enable_wall(&shared_wall_dir);
+    /// Disables the wall on both cells sharing it; outer walls are left untouched.
+    pub fn try_disable_wall(
+        &mut self,
+        coord: Coord,
+        direction: &TileDirection,
+    ) -> Result<(), MazeError> {
+        self.set_wall(coord, direction, false)
     }
 
-    pub fn disable_wall(&mut self, coord: Coord, direction: &TileDirection) {
+    fn set_wall(
+        &mut self,
+        coord: Coord,
+        direction: &TileDirection,
+        enabled: bool,
+    ) -> Result<(), MazeError> {
+        if !self.is_valid_coord(&coord) {
+            return Err(MazeError::OutOfBounds(coord));
+        }
         if self.is_edge_wall(coord, direction) {
-            return;
+            // sealed outer walls only open at the entrance and exit
+            if !self.sealed_border {
+                if enabled {
+                    self[coord].enable_wall(direction);
+                } else {
+                    self[coord].disable_wall(direction);
+                }
+            }
+            return Ok(());
         }
 
-        self.get_mut_cell(coord).unwrap().disable_wall(direction);
-
         let (neighbor_cell, shared_wall_dir) = self
             .get_mut_neighbor_cell_and_shared_wall(coord, direction)
-            .unwrap();
-        neighbor_cell.disable_wall(&shared_wall_dir);
+            .ok_or(MazeError::OutOfBounds(coord))?;
+        if enabled {
+            neighbor_cell.enable_wall(&shared_wall_dir);
+            self[coord].enable_wall(direction);
+        } else {
+            neighbor_cell.disable_wall(&shared_wall_dir);
+            self[coord].disable_wall(direction);
+        }
+
+        Ok(())
     }
 
     pub fn enable_all_walls(&mut self) {
@@ -206,6 +794,43 @@ impl Maze {
         }
     }
 
+    /// Opens the wall shared by two neighboring cells.
+    pub fn connect(&mut self, a: Coord, b: Coord) -> Result<(), ConnectError> {
+        let dir = self.get_shared_dir(a, b)?;
+        self.disable_wall(a, &dir);
+        Ok(())
+    }
+
+    /// Closes the wall shared by two neighboring cells.
+    pub fn disconnect(&mut self, a: Coord, b: Coord) -> Result<(), ConnectError> {
+        let dir = self.get_shared_dir(a, b)?;
+        self.enable_wall(a, &dir);
+        Ok(())
+    }
+
+    fn get_shared_dir(&self, a: Coord, b: Coord) -> Result<TileDirection, ConnectError> {
+        for coord in [a, b].iter() {
+            if !self.is_valid_coord(coord) {
+                return Err(ConnectError::OutOfBounds(*coord));
+            }
+        }
+
+        self.get_dir_between(a, b)
+            .ok_or(ConnectError::NotAdjacent(a, b))
+    }
+
+    /// Carves the passage of every `Carve` event, e.g. on a maze with all walls enabled
+    /// to rebuild the result of `MazeGen::generate_recorded`.
+    pub fn replay(&mut self, events: &[GenEvent]) {
+        for event in events {
+            if let GenEvent::Carve { from, to } = *event {
+                if let Some(dir) = self.get_dir_between(from, to) {
+                    self.disable_wall(from, &dir);
+                }
+            }
+        }
+    }
+
     pub fn disable_all_walls(&mut self) {
         for i in 0..self.size.width {
             for j in 0..self.size.height {
@@ -223,117 +848,3496 @@ impl Maze {
         // unsigned so no need to check if greater than zero
         coord.0 < self.size.width && coord.1 < self.size.height
     }
-}
 
-impl fmt::Display for Maze {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        // first line contains upper walls
-        let first_line = std::iter::repeat('_')
-            .take(self.size.width * 2 - 1)
-            .collect::<String>();
+    fn get_open_neighbor_coords(&self, coord: Coord) -> Vec<Coord> {
+        self.get_neighbor_coords_and_dirs(coord)
+            .into_iter()
+            .filter(|(_, dir)| !self.is_wall_enabled(coord, dir))
+            .map(|(coord, _)| coord)
+            .collect()
+    }
 
-        writeln!(f, " {}", first_line)?;
+    /// Every pair of neighboring cells joined by an open wall, each pair once.
+    pub fn passages(&self) -> impl Iterator<Item = (Coord, Coord)> + '_ {
+        (0..self.size.width)
+            .flat_map(move |i| (0..self.size.height).map(move |j| (i, j)))
+            .flat_map(move |coord| {
+                // east and south are enough to see every wall exactly once
+                self.get_neighbor_coords_and_dirs(coord)
+                    .into_iter()
+                    .filter(move |(_, dir)| {
+                        (*dir == TileDirection::EAST || *dir == TileDirection::SOUTH)
+                            && !self.is_wall_enabled(coord, dir)
+                    })
+                    .map(move |(next, _)| (coord, next))
+            })
+    }
 
-        for j in 0..self.size.height {
-            let mut first_line = String::new();
-            let mut second_line = String::new();
-            for i in 0..self.size.width - 1 {
-                let cell = self.get_cell((i, j)).unwrap();
+    /// Number of neighbors reachable in one move, outer walls counting as closed even at
+    /// the entrance and exit: 1 for dead ends, 2 for corridors, 3 or more for junctions.
+    pub fn passage_degree(&self, coord: Coord) -> usize {
+        self.get_open_neighbor_coords(coord).len()
+    }
 
-                // east wall line
-                first_line.push(' ');
-                if cell.is_wall_enabled(&TileDirection::EAST) {
-                    first_line.push('#');
-                } else {
-                    first_line.push(' ');
-                }
+    pub fn stats(&self) -> MazeStats {
+        let mut stats = MazeStats {
+            dead_ends: 0,
+            corridors: 0,
+            junctions: 0,
+            longest_corridor: 0,
+            passage_count: self.passages().count(),
+        };
 
-                // south wall line
-                if cell.is_wall_enabled(&TileDirection::SOUTH) {
-                    second_line.push('#');
-                } else {
-                    second_line.push(' ');
-                }
-                second_line.push('#');
+        let mut visited = BTreeSet::new();
+        for cell in self.iter_cells() {
+            match self.passage_degree(cell.coord) {
+                0 => {}
+                1 => stats.dead_ends += 1,
+                2 => stats.corridors += 1,
+                _ => stats.junctions += 1,
             }
-            writeln!(f, "|{} |", first_line)?;
-            if j < self.size.height - 1 {
-                writeln!(f, "|{}#|", second_line)?;
+
+            // flood the run of corridor cells this one belongs to
+            if self.passage_degree(cell.coord) != 2 || !visited.insert(cell.coord) {
+                continue;
+            }
+            let mut length = 0;
+            let mut stack = vec![cell.coord];
+            while let Some(coord) = stack.pop() {
+                length += 1;
+                for next in self.get_open_neighbor_coords(coord) {
+                    if self.passage_degree(next) == 2 && visited.insert(next) {
+                        stack.push(next);
+                    }
+                }
             }
+            stats.longest_corridor = stats.longest_corridor.max(length);
         }
 
-        Ok(())
+        stats
     }
-}
 
-pub struct MazeGen {
-    pub maze: Maze,
-    left_to_visit: HashSet<Coord>,
-    path_stack: Vec<Coord>,
-}
+    /// Passage graph as adjacency lists: every cell mapped to the cells it opens onto.
+    pub fn adjacency(&self) -> BTreeMap<Coord, Vec<Coord>> {
+        self.iter_cells()
+            .map(|cell| (cell.coord, self.get_open_neighbor_coords(cell.coord)))
+            .collect()
+    }
 
-impl MazeGen {
-    pub fn new(size: &Size) -> Self {
-        let mut left_to_visit = HashSet::new();
-        for i in 0..size.width {
-            for j in 0..size.height {
-                left_to_visit.insert((i, j));
-            }
+    /// True when the passages form a single tree spanning every cell: connected, and
+    /// exactly one passage fewer than there are cells.
+    pub fn is_perfect(&self) -> bool {
+        let cell_count = self.size.width * self.size.height;
+        if cell_count == 0 || self.passages().count() != cell_count - 1 {
+            return false;
         }
 
+        self.distances((0, 0))
+            .iter()
+            .flatten()
+            .all(|distance| distance.is_some())
+    }
+
+    fn is_sealed(&self, coord: Coord) -> bool {
+        ALL_TILE_DIRECTIONS
+            .iter()
+            .all(|dir| self.is_wall_enabled(coord, dir))
+    }
+
+    fn path_exists(&self, start: Coord, end: Coord) -> bool {
+        let mut visited = BTreeSet::new();
+        let mut queue = VecDeque::new();
+        visited.insert(start);
+        queue.push_back(start);
+
+        while let Some(coord) = queue.pop_front() {
+            if coord == end {
+                return true;
+            }
+            for next in self.get_open_neighbor_coords(coord) {
+                if visited.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Shortest sequence of cells from `start` to `goal` (both included) moving only
+    /// through open passages, or `None` if `goal` can't be reached.
+    pub fn solve_bfs(&self, start: Coord, goal: Coord) -> Option<Vec<Coord>> {
+        self.solve_observed(start, goal, |_, _| {})
+    }
+
+    /// Dead-end filling: cells other than `start` and `goal` with a single unfilled
+    /// neighbor are filled until none is left, then the route is traced through what remains.
+    pub fn solve_dead_end_fill(&self, start: Coord, goal: Coord) -> Option<Vec<Coord>> {
+        if !self.is_valid_coord(&start) || !self.is_valid_coord(&goal) {
+            return None;
+        }
+
+        let mut filled = vec![vec![false; self.size.height]; self.size.width];
+        let mut degrees = vec![vec![0; self.size.height]; self.size.width];
+        let mut dead_ends = Vec::new();
+        for cell in self.iter_cells() {
+            let (x, y) = cell.coord;
+            degrees[x][y] = self.get_open_neighbor_coords(cell.coord).len();
+            if degrees[x][y] <= 1 && cell.coord != start && cell.coord != goal {
+                dead_ends.push(cell.coord);
+            }
+        }
+
+        while let Some(coord) = dead_ends.pop() {
+            filled[coord.0][coord.1] = true;
+            for next in self.get_open_neighbor_coords(coord) {
+                if filled[next.0][next.1] {
+                    continue;
+                }
+                degrees[next.0][next.1] -= 1;
+                if degrees[next.0][next.1] == 1 && next != start && next != goal {
+                    dead_ends.push(next);
+                }
+            }
+        }
+
+        // loops survive the filling, so trace the shortest route among unfilled cells
+        let mut parents = BTreeMap::new();
+        let mut queue = VecDeque::new();
+        parents.insert(start, start);
+        queue.push_back(start);
+        while let Some(coord) = queue.pop_front() {
+            if coord == goal {
+                let mut path = vec![goal];
+                let mut current = goal;
+                while current != start {
+                    current = parents[&current];
+                    path.push(current);
+                }
+                path.reverse();
+
+                return Some(path);
+            }
+            for next in self.get_open_neighbor_coords(coord) {
+                if !filled[next.0][next.1] && !parents.contains_key(&next) {
+                    parents.insert(next, coord);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Right-hand rule: walks from `start` keeping a wall on its right until `goal` is
+    /// reached, returning every cell stepped on, revisits included. Gives up with None
+    /// once it is back in a cell facing a direction it already had, i.e. going in circles.
+    pub fn solve_wall_follower(&self, start: Coord, goal: Coord) -> Option<Vec<Coord>> {
+        if !self.is_valid_coord(&start) || !self.is_valid_coord(&goal) {
+            return None;
+        }
+
+        let mut seen = BTreeSet::new();
+        let mut path = vec![start];
+        let mut coord = start;
+        let mut facing = TileDirection::NORTH;
+
+        while coord != goal {
+            if !seen.insert((coord, facing)) {
+                return None;
+            }
+
+            // right, straight ahead, left, then back
+            let right = Self::get_clockwise(&facing);
+            let turns = [
+                right,
+                facing,
+                Self::get_opposite(&right),
+                Self::get_opposite(&facing),
+            ];
+            let open = self.get_neighbor_coords_and_dirs(coord);
+            let (next, dir) = turns.iter().find_map(|turn| {
+                open.iter()
+                    .find(|(_, dir)| dir == turn && !self.is_wall_enabled(coord, dir))
+                    .copied()
+            })?;
+
+            coord = next;
+            facing = dir;
+            path.push(coord);
+        }
+
+        Some(path)
+    }
+
+    /// Number of distinct shortest routes from `start` to `goal`, 0 if unreachable.
+    /// Saturates at `usize::MAX` on very open grids.
+    pub fn count_shortest_paths(&self, start: Coord, goal: Coord) -> usize {
+        if !self.is_valid_coord(&start) || !self.is_valid_coord(&goal) {
+            return 0;
+        }
+
+        // a cell is only popped once every route of the previous layer reached it
+        let mut distances = vec![vec![None; self.size.height]; self.size.width];
+        let mut counts = vec![vec![0usize; self.size.height]; self.size.width];
+        let mut queue = VecDeque::new();
+        distances[start.0][start.1] = Some(0);
+        counts[start.0][start.1] = 1;
+        queue.push_back(start);
+
+        while let Some(coord) = queue.pop_front() {
+            if coord == goal {
+                break;
+            }
+            let distance = distances[coord.0][coord.1].unwrap();
+            let count = counts[coord.0][coord.1];
+            for next in self.get_open_neighbor_coords(coord) {
+                match distances[next.0][next.1] {
+                    None => {
+                        distances[next.0][next.1] = Some(distance + 1);
+                        counts[next.0][next.1] = count;
+                        queue.push_back(next);
+                    }
+                    Some(next_distance) if next_distance == distance + 1 => {
+                        counts[next.0][next.1] = counts[next.0][next.1].saturating_add(count);
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        counts[goal.0][goal.1]
+    }
+
+    /// BFS from `start` to `end`, calling `on_visit` with each cell and its distance as
+    /// the search reaches it, in BFS layer order (`end` included, search stops there).
+    pub fn solve_observed(
+        &self,
+        start: Coord,
+        end: Coord,
+        mut on_visit: impl FnMut(Coord, usize),
+    ) -> Option<Vec<Coord>> {
+        if !self.is_valid_coord(&start) || !self.is_valid_coord(&end) {
+            return None;
+        }
+
+        let mut parents = BTreeMap::new();
+        let mut queue = VecDeque::new();
+        parents.insert(start, start);
+        queue.push_back((start, 0));
+
+        while let Some((coord, distance)) = queue.pop_front() {
+            on_visit(coord, distance);
+
+            if coord == end {
+                let mut path = vec![end];
+                let mut current = end;
+                while current != start {
+                    current = parents[&current];
+                    path.push(current);
+                }
+                path.reverse();
+
+                return Some(path);
+            }
+            for next in self.get_open_neighbor_coords(coord) {
+                if let Entry::Vacant(entry) = parents.entry(next) {
+                    entry.insert(coord);
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// A* search with a Manhattan distance heuristic: same path length as `solve_bfs`,
+    /// usually exploring fewer cells.
+    pub fn solve_astar(&self, start: Coord, goal: Coord) -> Option<Vec<Coord>> {
+        if !self.is_valid_coord(&start) || !self.is_valid_coord(&goal) {
+            return None;
+        }
+
+        // shortest way around for wrapping axes, to stay admissible on a torus
+        let axis_distance = |a: usize, b: usize, length: usize, wraps: bool| {
+            let distance = (a as isize - b as isize).unsigned_abs();
+            if wraps {
+                distance.min(length - distance)
+            } else {
+                distance
+            }
+        };
+        let heuristic = |coord: Coord| {
+            axis_distance(coord.0, goal.0, self.size.width, self.wraps_horizontally())
+                + axis_distance(coord.1, goal.1, self.size.height, self.wraps_vertically())
+        };
+
+        let mut parents = BTreeMap::new();
+        let mut costs = BTreeMap::new();
+        let mut open = BinaryHeap::new();
+        costs.insert(start, 0);
+        open.push(Reverse((heuristic(start), 0, start)));
+
+        while let Some(Reverse((_, cost, coord))) = open.pop() {
+            if coord == goal {
+                let mut path = vec![goal];
+                let mut current = goal;
+                while current != start {
+                    current = parents[&current];
+                    path.push(current);
+                }
+                path.reverse();
+
+                return Some(path);
+            }
+            if cost > costs[&coord] {
+                // stale entry, a cheaper route to this cell was found since
+                continue;
+            }
+
+            for next in self.get_open_neighbor_coords(coord) {
+                let next_cost = cost + 1;
+                if costs.get(&next).is_none_or(|known| next_cost < *known) {
+                    costs.insert(next, next_cost);
+                    parents.insert(next, coord);
+                    open.push(Reverse((next_cost + heuristic(next), next_cost, next)));
+                }
+            }
+        }
+
+        None
+    }
+
+    // cells walked from `from` through `next` and onward along single-passage corridors,
+    // until a cell accepted by `stop` (included) or a dead end of the walk
+    fn trace_corridor(&self, from: Coord, next: Coord, stop: impl Fn(Coord) -> bool) -> Vec<Coord> {
+        let mut cells = vec![next];
+        let (mut previous, mut current) = (from, next);
+
+        while !stop(current) && current != from {
+            let following = self
+                .get_open_neighbor_coords(current)
+                .into_iter()
+                .find(|coord| *coord != previous);
+            match following {
+                None => break,
+                Some(following) => {
+                    previous = current;
+                    current = following;
+                    cells.push(current);
+                }
+            }
+        }
+
+        cells
+    }
+
+    /// Junction graph: every cell that isn't a corridor (`passage_degree` other than 2)
+    /// mapped to the cells of that kind it reaches through a corridor, with the number
+    /// of steps. Two junctions joined by several corridors appear once per corridor.
+    pub fn to_reduced_graph(&self) -> BTreeMap<Coord, Vec<(Coord, usize)>> {
+        let is_junction = |coord: Coord| self.passage_degree(coord) != 2;
+
+        self.iter_cells()
+            .map(|cell| cell.coord)
+            .filter(|coord| is_junction(*coord))
+            .map(|coord| {
+                let edges = self
+                    .get_open_neighbor_coords(coord)
+                    .into_iter()
+                    .map(|next| {
+                        let corridor = self.trace_corridor(coord, next, is_junction);
+                        (*corridor.last().unwrap(), corridor.len())
+                    })
+                    .collect();
+                (coord, edges)
+            })
+            .collect()
+    }
+
+    /// Shortest path like `solve_bfs`, found with Dijkstra on `to_reduced_graph` so long
+    /// corridors are crossed in a single step, then expanded back to cells. Endpoints in
+    /// the middle of a corridor split it in two. The graph is rebuilt on every call.
+    pub fn solve_reduced(&self, start: Coord, end: Coord) -> Option<Vec<Coord>> {
+        if !self.is_valid_coord(&start) || !self.is_valid_coord(&end) {
+            return None;
+        }
+        if start == end {
+            return Some(vec![start]);
+        }
+
+        let mut graph = self.to_reduced_graph();
+        let is_node =
+            |coord: Coord| coord == start || coord == end || self.passage_degree(coord) != 2;
+        let splits: Vec<Coord> = [start, end]
+            .iter()
+            .copied()
+            .filter(|coord| !graph.contains_key(coord))
+            .collect();
+        for coord in splits {
+            let mut edges = Vec::new();
+            for next in self.get_open_neighbor_coords(coord) {
+                let corridor = self.trace_corridor(coord, next, is_node);
+                let to = *corridor.last().unwrap();
+                edges.push((to, corridor.len()));
+                graph.entry(to).or_default().push((coord, corridor.len()));
+            }
+            graph.insert(coord, edges);
+        }
+
+        let mut parents: BTreeMap<Coord, (Coord, usize)> = BTreeMap::new();
+        let mut costs = BTreeMap::new();
+        let mut open = BinaryHeap::new();
+        costs.insert(start, 0);
+        open.push(Reverse((0, start)));
+
+        while let Some(Reverse((cost, node))) = open.pop() {
+            if node == end {
+                break;
+            }
+            if cost > costs[&node] {
+                continue;
+            }
+
+            for (next, length) in &graph[&node] {
+                let next_cost = cost + length;
+                if costs.get(next).is_none_or(|known| next_cost < *known) {
+                    costs.insert(*next, next_cost);
+                    parents.insert(*next, (node, *length));
+                    open.push(Reverse((next_cost, *next)));
+                }
+            }
+        }
+
+        // walk the hops back from the end, expanding each one into its corridor
+        parents.get(&end)?;
+        let mut path = vec![end];
+        let mut node = end;
+        while node != start {
+            let (parent, length) = parents[&node];
+            let mut corridor = self
+                .get_open_neighbor_coords(parent)
+                .into_iter()
+                .map(|next| {
+                    self.trace_corridor(parent, next, |coord| {
+                        coord == node || self.passage_degree(coord) != 2
+                    })
+                })
+                .find(|corridor| corridor.len() == length && corridor.last() == Some(&node))
+                .unwrap();
+            corridor.pop();
+            path.extend(corridor.into_iter().rev());
+            path.push(parent);
+            node = parent;
+        }
+        path.reverse();
+
+        Some(path)
+    }
+
+    /// Cells traversed by the shortest paths from `sources` to `end`, with the number of
+    /// paths going through each, most used first. Endpoints of a path don't count for it.
+    pub fn choke_points(&self, sources: &[Coord], end: Coord) -> Vec<(Coord, usize)> {
+        let mut usage: BTreeMap<Coord, usize> = BTreeMap::new();
+
+        for source in sources {
+            if let Some(path) = self.solve_bfs(*source, end) {
+                if path.len() > 2 {
+                    for coord in &path[1..path.len() - 1] {
+                        *usage.entry(*coord).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        let mut ranked: Vec<(Coord, usize)> = usage.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        ranked
+    }
+
+    /// Copy of the maze with new dimensions, see `resize`.
+    pub fn resized(&self, new_size: Size) -> Maze<T>
+    where
+        T: Clone + Default,
+    {
+        let mut resized = self.clone();
+        resized.resize(&new_size);
+        resized
+    }
+
+    /// Changes the dimensions in place: the overlapping top-left region keeps its walls
+    /// and data, new cells start fully walled with default data and cropped cells are
+    /// dropped. A sealed border is sealed again with the default entrance and exit, an
+    /// unsealed one stays unsealed with its outer walls open as after `new_with_border`.
+    pub fn resize(&mut self, new_size: &Size)
+    where
+        T: Default,
+    {
+        let mut resized = Maze::new_with_data(new_size, self.topology, |coord| {
+            self.get_mut_cell(coord)
+                .map_or_else(T::default, |cell| core::mem::take(&mut cell.data))
+        });
+        if !self.sealed_border {
+            resized.unseal_border();
+        }
+        resized.enable_all_walls();
+
+        let overlap_width = self.size.width.min(new_size.width);
+        let overlap_height = self.size.height.min(new_size.height);
+
+        for i in 0..overlap_width {
+            for j in 0..overlap_height {
+                // east and south are enough to cover every wall shared inside the overlap
+                if i + 1 < overlap_width && !self.is_wall_enabled((i, j), &TileDirection::EAST) {
+                    resized.disable_wall((i, j), &TileDirection::EAST);
+                }
+                if j + 1 < overlap_height && !self.is_wall_enabled((i, j), &TileDirection::SOUTH) {
+                    resized.disable_wall((i, j), &TileDirection::SOUTH);
+                }
+            }
+        }
+
+        *self = resized;
+    }
+
+    /// Copy rotated a quarter turn clockwise: a `w`x`h` maze becomes `h`x`w` and north
+    /// walls become east walls.
+    pub fn rotate_90(&self) -> Maze<T>
+    where
+        T: Clone,
+    {
+        let height = self.size.height;
+        let new_size = Size {
+            width: height,
+            height: self.size.width,
+        };
+        self.transformed(new_size, |(x, y)| (height - 1 - y, x), Self::get_clockwise)
+    }
+
+    /// Mirror copy, left to right.
+    pub fn flip_horizontal(&self) -> Maze<T>
+    where
+        T: Clone,
+    {
+        let width = self.size.width;
+        self.transformed(
+            self.size,
+            |(x, y)| (width - 1 - x, y),
+            |dir| match dir {
+                TileDirection::EAST | TileDirection::WEST => Self::get_opposite(dir),
+                _ => *dir,
+            },
+        )
+    }
+
+    /// Mirror copy, top to bottom.
+    pub fn flip_vertical(&self) -> Maze<T>
+    where
+        T: Clone,
+    {
+        let height = self.size.height;
+        self.transformed(
+            self.size,
+            |(x, y)| (x, height - 1 - y),
+            |dir| match dir {
+                TileDirection::NORTH | TileDirection::SOUTH => Self::get_opposite(dir),
+                _ => *dir,
+            },
+        )
+    }
+
+    // copy with every cell moved by `map_coord` and its walls turned by `map_dir`,
+    // openings included
+    fn transformed(
+        &self,
+        new_size: Size,
+        map_coord: impl Fn(Coord) -> Coord,
+        map_dir: impl Fn(&TileDirection) -> TileDirection,
+    ) -> Maze<T>
+    where
+        T: Clone,
+    {
+        let mut data: BTreeMap<Coord, T> = self
+            .iter_cells()
+            .map(|cell| (map_coord(cell.coord), cell.data.clone()))
+            .collect();
+        let mut maze = Maze::new_with_data(&new_size, self.topology, |coord| {
+            data.remove(&coord).unwrap()
+        });
+        if !self.sealed_border {
+            maze.unseal_border();
+        }
+
+        maze.set_entrance(map_coord(self.entrance), &map_dir(&self.entrance_dir));
+        maze.set_exit(map_coord(self.exit), &map_dir(&self.exit_dir));
+
+        // sealed outer walls ignore this, their openings were moved above
+        for cell in self.iter_cells() {
+            for dir in ALL_TILE_DIRECTIONS.iter() {
+                let (coord, new_dir) = (map_coord(cell.coord), map_dir(dir));
+                if self.is_wall_enabled(cell.coord, dir) {
+                    maze.enable_wall(coord, &new_dir);
+                } else {
+                    maze.disable_wall(coord, &new_dir);
+                }
+            }
+        }
+
+        maze
+    }
+
+    /// Spanning tree of the component containing `root`, as parent to child edges in
+    /// BFS order. On a perfect maze this is the maze itself.
+    pub fn tree_edges(&self, root: Coord) -> Vec<(Coord, Coord)> {
+        let mut edges = Vec::new();
+        let mut visited = BTreeSet::new();
+        let mut queue = VecDeque::new();
+        visited.insert(root);
+        queue.push_back(root);
+
+        while let Some(coord) = queue.pop_front() {
+            for next in self.get_open_neighbor_coords(coord) {
+                if visited.insert(next) {
+                    edges.push((coord, next));
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        edges
+    }
+
+    /// Minimum number of open passages to close to disconnect `start` from `end`, i.e.
+    /// how many edge-disjoint routes join them. A perfect maze always gives 1 (0 when
+    /// `start == end`).
+    pub fn min_cut(&self, start: Coord, end: Coord) -> usize {
+        if start == end {
+            return 0;
+        }
+
+        // max-flow with unit capacities: every open passage can carry one unit each way,
+        // `flow[(a, b)]` is the net flow from a to b
+        let mut flow: BTreeMap<(Coord, Coord), i32> = BTreeMap::new();
+        let mut cut = 0;
+
+        loop {
+            let mut parents = BTreeMap::new();
+            let mut queue = VecDeque::new();
+            parents.insert(start, start);
+            queue.push_back(start);
+
+            while let Some(coord) = queue.pop_front() {
+                if coord == end {
+                    break;
+                }
+                for next in self.get_open_neighbor_coords(coord) {
+                    let residual = 1 - flow.get(&(coord, next)).copied().unwrap_or(0);
+                    if residual > 0 && !parents.contains_key(&next) {
+                        parents.insert(next, coord);
+                        queue.push_back(next);
+                    }
+                }
+            }
+
+            if !parents.contains_key(&end) {
+                return cut;
+            }
+
+            let mut current = end;
+            while current != start {
+                let previous = parents[&current];
+                *flow.entry((previous, current)).or_insert(0) += 1;
+                *flow.entry((current, previous)).or_insert(0) -= 1;
+                current = previous;
+            }
+            cut += 1;
+        }
+    }
+
+    /// True if `from` and `to` share a row or column and every passage between them is
+    /// open. A cell has no line of sight to itself.
+    pub fn has_line_of_sight(&self, from: Coord, to: Coord) -> bool {
+        if from == to || !self.is_valid_coord(&from) || !self.is_valid_coord(&to) {
+            return false;
+        }
+
+        if from.1 == to.1 {
+            let (start, end) = (from.0.min(to.0), from.0.max(to.0));
+            (start..end).all(|i| !self.is_wall_enabled((i, from.1), &TileDirection::EAST))
+        } else if from.0 == to.0 {
+            let (start, end) = (from.1.min(to.1), from.1.max(to.1));
+            (start..end).all(|j| !self.is_wall_enabled((from.0, j), &TileDirection::SOUTH))
+        } else {
+            false
+        }
+    }
+
+    /// Waypoints of `path` with the intermediate cells removed wherever the next kept
+    /// cell is in line of sight (`has_line_of_sight`) of the previous one, so straight
+    /// runs collapse to their ends. The first and last cells are always kept.
+    pub fn smooth_path(&self, path: &[Coord]) -> Vec<Coord> {
+        let mut waypoints: Vec<Coord> = path.iter().take(1).copied().collect();
+
+        let mut index = 0;
+        while index + 1 < path.len() {
+            // farthest cell still in sight, or at least the next one
+            index = (index + 2..path.len())
+                .rev()
+                .find(|next| self.has_line_of_sight(path[index], path[*next]))
+                .unwrap_or(index + 1);
+            waypoints.push(path[index]);
+        }
+
+        waypoints
+    }
+
+    /// Number of passage steps from `source` to every cell, indexed [x][y], with `None`
+    /// for cells that can't be reached.
+    pub fn distances(&self, source: Coord) -> Vec<Vec<Option<usize>>> {
+        let mut distances = vec![vec![None; self.size.height]; self.size.width];
+        if !self.is_valid_coord(&source) {
+            return distances;
+        }
+
+        let mut queue = VecDeque::new();
+        distances[source.0][source.1] = Some(0);
+        queue.push_back((source, 0));
+
+        while let Some((coord, distance)) = queue.pop_front() {
+            for next in self.get_open_neighbor_coords(coord) {
+                if distances[next.0][next.1].is_none() {
+                    distances[next.0][next.1] = Some(distance + 1);
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
+
+        distances
+    }
+
+    /// Number of cells reachable from `from` through open passages, `from` included;
+    /// 0 if it isn't a valid cell.
+    pub fn reachable_count(&self, from: Coord) -> usize {
+        self.distances(from)
+            .iter()
+            .flatten()
+            .filter(|distance| distance.is_some())
+            .count()
+    }
+
+    // reachable cell farthest from `source`, with its distance
+    fn farthest_from(&self, source: Coord) -> (Coord, usize) {
+        let mut farthest = (source, 0);
+        for (i, column) in self.distances(source).iter().enumerate() {
+            for (j, distance) in column.iter().enumerate() {
+                if let Some(distance) = distance {
+                    if *distance > farthest.1 {
+                        farthest = ((i, j), *distance);
+                    }
+                }
+            }
+        }
+
+        farthest
+    }
+
+    /// Path between the two cells farthest apart (the diameter of the passage graph),
+    /// found with a BFS from (0, 0) to the farthest cell, then a BFS from there.
+    /// Exact on perfect mazes.
+    pub fn longest_path(&self) -> Vec<Coord> {
+        let (start, _) = self.farthest_from((0, 0));
+        let (end, _) = self.farthest_from(start);
+
+        self.solve_bfs(start, end).unwrap()
+    }
+
+    /// For every cell, the direction to step in to get closer to `goal` along a shortest
+    /// route, indexed [x][y]. `None` at the goal itself and on cells that can't reach it.
+    pub fn flow_field(&self, goal: Coord) -> Vec<Vec<Option<TileDirection>>> {
+        let distances = self.distances(goal);
+
+        (0..self.size.width)
+            .map(|i| {
+                (0..self.size.height)
+                    .map(|j| {
+                        let distance = distances[i][j].filter(|distance| *distance > 0)?;
+                        self.get_neighbor_coords_and_dirs((i, j))
+                            .into_iter()
+                            .filter(|(_, dir)| !self.is_wall_enabled((i, j), dir))
+                            .find(|(next, _)| distances[next.0][next.1] == Some(distance - 1))
+                            .map(|(_, dir)| dir)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Expanded `(2 * width + 1) x (2 * height + 1)` representation indexed `[x][y]`,
+    /// true for walls: cells sit at odd/odd positions and are always open, walls between
+    /// them, and posts at even/even positions are always walls.
+    pub fn to_wall_grid(&self) -> Vec<Vec<bool>> {
+        let mut grid = vec![vec![true; 2 * self.size.height + 1]; 2 * self.size.width + 1];
+
+        for i in 0..self.size.width {
+            for j in 0..self.size.height {
+                let (x, y) = (2 * i + 1, 2 * j + 1);
+                grid[x][y] = false;
+                grid[x][y - 1] = self.is_wall_enabled((i, j), &TileDirection::NORTH);
+                grid[x + 1][y] = self.is_wall_enabled((i, j), &TileDirection::EAST);
+                grid[x][y + 1] = self.is_wall_enabled((i, j), &TileDirection::SOUTH);
+                grid[x - 1][y] = self.is_wall_enabled((i, j), &TileDirection::WEST);
+            }
+        }
+
+        grid
+    }
+
+    /// Cave-like smoothing on the `to_wall_grid` representation: about half the inner
+    /// positions are rerolled as walls with a 45% chance, then `iterations` passes of
+    /// the 4-5 cellular automaton rule (a position becomes a wall when at least 5 of
+    /// the 9 around and including it are walls) grow caverns out of the open space.
+    /// Passages are read back between open cells and filled cells are walled in on all
+    /// sides; `MazeGen::complete` reconnects them.
+    pub fn cave_smooth(&mut self, iterations: usize, seed: u64) {
+        const REROLL_CHANCE: f64 = 0.5;
+        const WALL_CHANCE: f64 = 0.45;
+
+        let mut rng = Pcg64::seed_from_u64(seed);
+        let mut grid = self.to_wall_grid();
+        let (grid_width, grid_height) = (grid.len(), grid[0].len());
+
+        // the outer ring of the grid is left as it is
+        for column in grid.iter_mut().take(grid_width - 1).skip(1) {
+            for position in column.iter_mut().take(grid_height - 1).skip(1) {
+                if rng.gen_bool(REROLL_CHANCE) {
+                    *position = rng.gen_bool(WALL_CHANCE);
+                }
+            }
+        }
+
+        for _ in 0..iterations {
+            let previous = grid.clone();
+            for (x, column) in grid.iter_mut().enumerate().take(grid_width - 1).skip(1) {
+                for (y, position) in column.iter_mut().enumerate().take(grid_height - 1).skip(1) {
+                    let walls = (x - 1..=x + 1)
+                        .flat_map(|i| (y - 1..=y + 1).map(move |j| (i, j)))
+                        .filter(|(i, j)| previous[*i][*j])
+                        .count();
+                    *position = walls >= 5;
+                }
+            }
+        }
+
+        for i in 0..self.size.width {
+            for j in 0..self.size.height {
+                let (x, y) = (2 * i + 1, 2 * j + 1);
+                if i + 1 < self.size.width {
+                    let open = !grid[x][y] && !grid[x + 1][y] && !grid[x + 2][y];
+                    self.set_wall((i, j), &TileDirection::EAST, !open).unwrap();
+                }
+                if j + 1 < self.size.height {
+                    let open = !grid[x][y] && !grid[x][y + 1] && !grid[x][y + 2];
+                    self.set_wall((i, j), &TileDirection::SOUTH, !open).unwrap();
+                }
+            }
+        }
+        for i in 0..self.size.width {
+            for j in 0..self.size.height {
+                if grid[2 * i + 1][2 * j + 1] {
+                    for dir in ALL_TILE_DIRECTIONS.iter() {
+                        self.enable_wall((i, j), dir);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Renders the expanded wall grid with every position exactly two characters wide
+    /// (`##` for walls, two spaces for open space), so all lines have the same length.
+    pub fn to_ascii_aligned(&self) -> String {
+        let grid = self.to_wall_grid();
+        let mut ascii = String::new();
+
+        for y in 0..grid[0].len() {
+            for column in &grid {
+                ascii.push_str(if column[y] { "##" } else { "  " });
+            }
+            ascii.push('\n');
+        }
+
+        ascii
+    }
+
+    /// Same layout as `to_ascii_aligned` with colors through ANSI escape codes: walls on
+    /// a white background, and the cells of `path` plus the passages between consecutive
+    /// ones on a red background.
+    pub fn render_ansi(&self, path: Option<&[Coord]>) -> String {
+        const WALL: &str = "\x1b[47m  \x1b[0m";
+        const PATH: &str = "\x1b[41m  \x1b[0m";
+
+        let path = path.unwrap_or(&[]);
+        let mut highlighted = BTreeSet::new();
+        for coord in path {
+            highlighted.insert((2 * coord.0 + 1, 2 * coord.1 + 1));
+        }
+        for pair in path.windows(2) {
+            let ((x1, y1), (x2, y2)) = (pair[0], pair[1]);
+            // skip jumps and moves across a torus seam, their wall isn't between them
+            if x1.abs_diff(x2) + y1.abs_diff(y2) == 1 {
+                highlighted.insert((x1 + x2 + 1, y1 + y2 + 1));
+            }
+        }
+
+        let grid = self.to_wall_grid();
+        let mut ansi = String::new();
+        for y in 0..grid[0].len() {
+            for (x, column) in grid.iter().enumerate() {
+                ansi.push_str(if column[y] {
+                    WALL
+                } else if highlighted.contains(&(x, y)) {
+                    PATH
+                } else {
+                    "  "
+                });
+            }
+            ansi.push('\n');
+        }
+
+        ansi
+    }
+
+    /// Cells with exactly one open passage, outer walls counting as closed.
+    pub fn dead_ends(&self) -> Vec<Coord> {
+        (0..self.size.width)
+            .flat_map(|i| (0..self.size.height).map(move |j| (i, j)))
+            .filter(|coord| self.get_open_neighbor_coords(*coord).len() == 1)
+            .collect()
+    }
+
+    /// Cells without any open passage (`passage_degree` of 0), e.g. masked out cells or
+    /// ones sealed off by manual edits.
+    pub fn isolated_cells(&self) -> Vec<Coord> {
+        (0..self.size.width)
+            .flat_map(|i| (0..self.size.height).map(move |j| (i, j)))
+            .filter(|coord| self.passage_degree(*coord) == 0)
+            .collect()
+    }
+
+    /// Adds loops: a random `ratio` of the dead ends get one extra wall carved,
+    /// preferably toward another dead end. A ratio of 1.0 removes nearly all of them.
+    pub fn braid(&mut self, seed: u64, ratio: f64) {
+        let mut rng = Pcg64::seed_from_u64(seed);
+
+        let dead_ends = self.dead_ends();
+        self.braid_some(&mut rng, dead_ends, ratio, &BTreeSet::new());
+    }
+
+    // carves a random `ratio` of `dead_ends` as in `braid`, never into `blocked` cells
+    fn braid_some(
+        &mut self,
+        rng: &mut Pcg64,
+        mut dead_ends: Vec<Coord>,
+        ratio: f64,
+        blocked: &BTreeSet<Coord>,
+    ) {
+        dead_ends.shuffle(rng);
+        // rounded to the nearest, `f64::round` needing std
+        let count = (dead_ends.len() as f64 * ratio.clamp(0.0, 1.0) + 0.5) as usize;
+
+        for coord in dead_ends.into_iter().take(count) {
+            // carving an earlier dead end may have fixed this one already
+            if self.get_open_neighbor_coords(coord).len() != 1 {
+                continue;
+            }
+
+            let walled: Vec<(Coord, TileDirection)> = self
+                .get_neighbor_coords_and_dirs(coord)
+                .into_iter()
+                .filter(|(next, dir)| self.is_wall_enabled(coord, dir) && !blocked.contains(next))
+                .collect();
+            let (towards_dead_ends, others): (Vec<_>, Vec<_>) = walled
+                .into_iter()
+                .partition(|(next, _)| self.get_open_neighbor_coords(*next).len() == 1);
+
+            let chosen = towards_dead_ends.choose(rng).or_else(|| others.choose(rng));
+            if let Some((_, dir)) = chosen {
+                self.disable_wall(coord, dir);
+            }
+        }
+    }
+
+    /// Preflight check: both endpoints are in bounds, neither is fully walled in,
+    /// and a path of open passages connects them.
+    pub fn is_solvable(&self, start: Coord, end: Coord) -> bool {
+        if !self.is_valid_coord(&start) || !self.is_valid_coord(&end) {
+            return false;
+        }
+        if self.is_sealed(start) || self.is_sealed(end) {
+            return false;
+        }
+
+        self.path_exists(start, end)
+    }
+
+    /// True when `to` can be reached from `from` through open passages, a cell always
+    /// reaching itself. The search stops as soon as `to` is found.
+    pub fn is_reachable(&self, from: Coord, to: Coord) -> bool {
+        self.is_valid_coord(&from) && self.is_valid_coord(&to) && self.path_exists(from, to)
+    }
+
+    // one <line> per enabled wall, each shared wall drawn only once
+    fn svg_wall_lines(&self, cell_size: f64, offset: (f64, f64), stroke_width: f64) -> String {
+        let mut lines = String::new();
+
+        for i in 0..self.size.width {
+            for j in 0..self.size.height {
+                let x = offset.0 + i as f64 * cell_size;
+                let y = offset.1 + j as f64 * cell_size;
+
+                let mut segments = Vec::new();
+                if self.is_wall_enabled((i, j), &TileDirection::NORTH) {
+                    segments.push((x, y, x + cell_size, y));
+                }
+                if self.is_wall_enabled((i, j), &TileDirection::WEST) {
+                    segments.push((x, y, x, y + cell_size));
+                }
+                if i == self.size.width - 1 && self.is_wall_enabled((i, j), &TileDirection::EAST) {
+                    segments.push((x + cell_size, y, x + cell_size, y + cell_size));
+                }
+                if j == self.size.height - 1 && self.is_wall_enabled((i, j), &TileDirection::SOUTH)
+                {
+                    segments.push((x, y + cell_size, x + cell_size, y + cell_size));
+                }
+
+                for (x1, y1, x2, y2) in segments {
+                    lines.push_str(&format!(
+                        "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"black\" stroke-width=\"{}\" stroke-linecap=\"square\"/>\n",
+                        x1, y1, x2, y2, stroke_width
+                    ));
+                }
+            }
+        }
+
+        lines
+    }
+
+    /// SVG of the maze where the shortest path from `start` to `end` draws itself with a
+    /// SMIL animation of its stroke-dashoffset. Without a solution only the walls are drawn.
+    pub fn to_svg_animated_solution(&self, start: Coord, end: Coord) -> String {
+        const CELL_SIZE: f64 = 20.0;
+        const WALL_THICKNESS: f64 = 2.0;
+        const SECONDS_PER_STEP: f64 = 0.1;
+
+        let width = self.size.width as f64 * CELL_SIZE + 2.0 * WALL_THICKNESS;
+        let height = self.size.height as f64 * CELL_SIZE + 2.0 * WALL_THICKNESS;
+        let offset = (WALL_THICKNESS, WALL_THICKNESS);
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+            w = width,
+            h = height,
+        );
+        svg.push_str(&self.svg_wall_lines(CELL_SIZE, offset, WALL_THICKNESS));
+
+        if let Some(path) = self.solve_bfs(start, end) {
+            let points = path
+                .iter()
+                .enumerate()
+                .map(|(index, coord)| {
+                    format!(
+                        "{}{} {}",
+                        if index == 0 { "M" } else { " L" },
+                        offset.0 + (coord.0 as f64 + 0.5) * CELL_SIZE,
+                        offset.1 + (coord.1 as f64 + 0.5) * CELL_SIZE,
+                    )
+                })
+                .collect::<String>();
+
+            // consecutive cells are adjacent, so every segment is one cell long
+            let length = (path.len() - 1) as f64 * CELL_SIZE;
+            let duration = ((path.len() - 1) as f64 * SECONDS_PER_STEP).max(SECONDS_PER_STEP);
+
+            svg.push_str(&format!(
+                "<path d=\"{}\" fill=\"none\" stroke=\"red\" stroke-width=\"{}\" stroke-linecap=\"round\" stroke-linejoin=\"round\" stroke-dasharray=\"{len}\" stroke-dashoffset=\"{len}\">\n",
+                points,
+                CELL_SIZE / 4.0,
+                len = length,
+            ));
+            svg.push_str(&format!(
+                "<animate attributeName=\"stroke-dashoffset\" from=\"{}\" to=\"0\" dur=\"{}s\" fill=\"freeze\"/>\n",
+                length, duration
+            ));
+            svg.push_str("</path>\n");
+        }
+
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// Walls merged into maximal straight runs, as `(x, y, width, height)` rectangles
+    /// with zero thickness (horizontal runs have a height of 0, vertical runs a width of 0).
+    /// Inflate them by the wall thickness to get physics colliders.
+    pub fn wall_rects_merged(&self, cell_size: f64) -> Vec<(f64, f64, f64, f64)> {
+        let mut rects = Vec::new();
+
+        // horizontal grid lines, the last one being the south border
+        for line in 0..=self.size.height {
+            let mut run_start = None;
+            for i in 0..=self.size.width {
+                let has_wall = i < self.size.width
+                    && if line < self.size.height {
+                        self.is_wall_enabled((i, line), &TileDirection::NORTH)
+                    } else {
+                        self.is_wall_enabled((i, line - 1), &TileDirection::SOUTH)
+                    };
+
+                match (has_wall, run_start) {
+                    (true, None) => run_start = Some(i),
+                    (false, Some(start)) => {
+                        rects.push((
+                            start as f64 * cell_size,
+                            line as f64 * cell_size,
+                            (i - start) as f64 * cell_size,
+                            0.0,
+                        ));
+                        run_start = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        // vertical grid lines, the last one being the east border
+        for line in 0..=self.size.width {
+            let mut run_start = None;
+            for j in 0..=self.size.height {
+                let has_wall = j < self.size.height
+                    && if line < self.size.width {
+                        self.is_wall_enabled((line, j), &TileDirection::WEST)
+                    } else {
+                        self.is_wall_enabled((line - 1, j), &TileDirection::EAST)
+                    };
+
+                match (has_wall, run_start) {
+                    (true, None) => run_start = Some(j),
+                    (false, Some(start)) => {
+                        rects.push((
+                            line as f64 * cell_size,
+                            start as f64 * cell_size,
+                            0.0,
+                            (j - start) as f64 * cell_size,
+                        ));
+                        run_start = None;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        rects
+    }
+
+    /// Stable 64-bit hash of the maze structure, identical across runs and platforms.
+    ///
+    /// FNV-1a (64-bit) over the width and height as little-endian `u64`s, followed by
+    /// one byte per cell in `[x][y]` order whose low four bits are the NORTH, EAST,
+    /// SOUTH and WEST walls as reported by `is_wall_enabled`.
+    pub fn fingerprint(&self) -> u64 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+        let mut hash = FNV_OFFSET_BASIS;
+        let mut feed = |byte: u8| {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        };
+
+        for byte in (self.size.width as u64)
+            .to_le_bytes()
+            .iter()
+            .chain((self.size.height as u64).to_le_bytes().iter())
+        {
+            feed(*byte);
+        }
+
+        for i in 0..self.size.width {
+            for j in 0..self.size.height {
+                let mut bits = 0u8;
+                for (bit, dir) in ALL_TILE_DIRECTIONS.iter().enumerate() {
+                    if self.is_wall_enabled((i, j), dir) {
+                        bits |= 1 << bit;
+                    }
+                }
+                feed(bits);
+            }
+        }
+
+        hash
+    }
+
+    /// Graphviz description of the passage graph, one node per cell and one undirected
+    /// edge per open passage, e.g. for `dot -Tpng`.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("graph maze {\n");
+        for cell in self.iter_cells() {
+            let (x, y) = cell.coord;
+            dot.push_str(&format!("    c{}_{} [label=\"({}, {})\"];\n", x, y, x, y));
+        }
+        for ((x1, y1), (x2, y2)) in self.passages() {
+            dot.push_str(&format!("    c{}_{} -- c{}_{};\n", x1, y1, x2, y2));
+        }
+        dot.push_str("}\n");
+
+        dot
+    }
+
+    /// Wall states as CSV, a header then one `x,y,north,east,south,west` row per cell in
+    /// storage order, each wall being 1 when enabled and 0 when open.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("x,y,north,east,south,west\n");
+        for cell in self.iter_cells() {
+            let (x, y) = cell.coord;
+            csv.push_str(&format!("{},{}", x, y));
+            for dir in ALL_TILE_DIRECTIONS.iter() {
+                csv.push_str(if self.is_wall_enabled(cell.coord, dir) {
+                    ",1"
+                } else {
+                    ",0"
+                });
+            }
+            csv.push('\n');
+        }
+
+        csv
+    }
+
+    /// The `to_wall_grid` as a monochrome Windows bitmap, the bitmap format the Daedalus
+    /// maze program opens, one pixel per grid position with set pixels being walls.
+    ///
+    /// All integers are little-endian. 14-byte file header: `BM`, file size (u32), two
+    /// reserved u16 zeros, pixel data offset 62 (u32). 40-byte info header: its size 40,
+    /// width `2 * width + 1` and height `2 * height + 1` (i32), 1 plane, 1 bit per pixel
+    /// (u16 each), no compression, pixel data size, 2835 pixels per meter both ways, 2
+    /// palette colors, 2 important (u32 each). Palette: index 0 white, index 1 black, as
+    /// BGRA. Pixel rows then go bottom to top, leftmost pixel in the highest bit, each row
+    /// padded with zeros to a multiple of 4 bytes.
+    pub fn to_daedalus(&self) -> Vec<u8> {
+        const HEADERS_SIZE: u32 = 14 + 40 + 8;
+        const PIXELS_PER_METER: u32 = 2835;
+
+        let grid = self.to_wall_grid();
+        let (grid_width, grid_height) = (grid.len(), grid[0].len());
+        let row_size = grid_width.div_ceil(32) * 4;
+        let pixels_size = (row_size * grid_height) as u32;
+
+        let mut bytes = Vec::with_capacity((HEADERS_SIZE + pixels_size) as usize);
+        bytes.extend_from_slice(b"BM");
+        bytes.extend_from_slice(&(HEADERS_SIZE + pixels_size).to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(&HEADERS_SIZE.to_le_bytes());
+
+        bytes.extend_from_slice(&40u32.to_le_bytes());
+        bytes.extend_from_slice(&(grid_width as i32).to_le_bytes());
+        bytes.extend_from_slice(&(grid_height as i32).to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&0u32.to_le_bytes());
+        bytes.extend_from_slice(&pixels_size.to_le_bytes());
+        bytes.extend_from_slice(&PIXELS_PER_METER.to_le_bytes());
+        bytes.extend_from_slice(&PIXELS_PER_METER.to_le_bytes());
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+
+        bytes.extend_from_slice(&[0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0x00]);
+
+        for y in (0..grid_height).rev() {
+            let mut row = vec![0u8; row_size];
+            for (x, column) in grid.iter().enumerate() {
+                if column[y] {
+                    row[x / 8] |= 0x80 >> (x % 8);
+                }
+            }
+            bytes.extend_from_slice(&row);
+        }
+
+        bytes
+    }
+
+    /// Self-contained SVG document with a line for every enabled wall, borders included.
+    /// Cell (x, y) spans `[x * cell_size, (x + 1) * cell_size]` horizontally, shifted by
+    /// half the wall thickness so border walls aren't clipped.
+    pub fn to_svg(&self, cell_size: f64, wall_thickness: f64) -> String {
+        self.to_svg_with(cell_size, wall_thickness, &SvgOptions::default())
+    }
+
+    /// `to_svg` with the extras enabled in `options`. Cell labels are centered and sized
+    /// so the longest one still fits its cell.
+    pub fn to_svg_with(&self, cell_size: f64, wall_thickness: f64, options: &SvgOptions) -> String {
+        let width = self.size.width as f64 * cell_size + wall_thickness;
+        let height = self.size.height as f64 * cell_size + wall_thickness;
+        let offset = (wall_thickness / 2.0, wall_thickness / 2.0);
+
+        let mut labels = String::new();
+        if options.label_cells {
+            // glyphs are about 0.6em wide, keep a margin on both sides
+            let longest = format!("{},{}", self.size.width - 1, self.size.height - 1).len();
+            let font_size = (cell_size * 0.8 / (0.6 * longest as f64)).min(cell_size / 3.0);
+            for i in 0..self.size.width {
+                for j in 0..self.size.height {
+                    labels.push_str(&format!(
+                        "<text x=\"{}\" y=\"{}\" font-size=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">{},{}</text>\n",
+                        offset.0 + (i as f64 + 0.5) * cell_size,
+                        offset.1 + (j as f64 + 0.5) * cell_size,
+                        font_size,
+                        i,
+                        j,
+                    ));
+                }
+            }
+        }
+
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n{}{}</svg>\n",
+            self.svg_wall_lines(cell_size, offset, wall_thickness),
+            labels,
+            w = width,
+            h = height,
+        )
+    }
+
+    /// Renders the maze scaled to fit `canvas` (minus `padding` on every side),
+    /// preserving the aspect ratio and centering it.
+    pub fn to_svg_fixed(&self, canvas: Size, padding: f64) -> String {
+        let available_width = (canvas.width as f64 - 2.0 * padding).max(0.0);
+        let available_height = (canvas.height as f64 - 2.0 * padding).max(0.0);
+
+        let cell_size = f64::min(
+            available_width / self.size.width as f64,
+            available_height / self.size.height as f64,
+        );
+
+        let offset = (
+            (canvas.width as f64 - cell_size * self.size.width as f64) / 2.0,
+            (canvas.height as f64 - cell_size * self.size.height as f64) / 2.0,
+        );
+
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n{}</svg>\n",
+            self.svg_wall_lines(cell_size, offset, (cell_size / 10.0).max(1.0)),
+            w = canvas.width,
+            h = canvas.height,
+        )
+    }
+}
+
+#[cfg(feature = "image")]
+impl<T> Maze<T> {
+    /// Rasterizes the walls as black pixels on a white background. The image is
+    /// `width * cell_size + wall_thickness` by `height * cell_size + wall_thickness`,
+    /// each wall covering `wall_thickness` pixels from the cell's edge.
+    pub fn to_image(&self, cell_size: u32, wall_thickness: u32) -> image::RgbaImage {
+        let white = image::Rgba([255, 255, 255, 255]);
+        let black = image::Rgba([0, 0, 0, 255]);
+
+        let mut img = image::RgbaImage::from_pixel(
+            self.size.width as u32 * cell_size + wall_thickness,
+            self.size.height as u32 * cell_size + wall_thickness,
+            white,
+        );
+
+        for i in 0..self.size.width {
+            for j in 0..self.size.height {
+                let (x, y) = (i as u32 * cell_size, j as u32 * cell_size);
+                let full_length = cell_size + wall_thickness;
+
+                for direction in ALL_TILE_DIRECTIONS.iter() {
+                    if !self.is_wall_enabled((i, j), direction) {
+                        continue;
+                    }
+
+                    // (x, y, width, height) of the wall
+                    let (wall_x, wall_y, wall_width, wall_height) = match direction {
+                        TileDirection::NORTH => (x, y, full_length, wall_thickness),
+                        TileDirection::WEST => (x, y, wall_thickness, full_length),
+                        TileDirection::SOUTH => (x, y + cell_size, full_length, wall_thickness),
+                        TileDirection::EAST => (x + cell_size, y, wall_thickness, full_length),
+                    };
+
+                    for px in wall_x..wall_x + wall_width {
+                        for py in wall_y..wall_y + wall_height {
+                            img.put_pixel(px, py, black);
+                        }
+                    }
+                }
+            }
+        }
+
+        img
+    }
+}
+
+impl<T> Maze<T> {
+    /// Same rendering as `Display`, with the cells of `path` marked by a `*`. The path
+    /// doesn't have to be connected, every listed cell is marked.
+    pub fn render_with_path(&self, path: &[Coord]) -> String {
+        let mut rendered = String::new();
+        self.write_ascii(&mut rendered, path).unwrap();
+
+        rendered
+    }
+
+    fn write_ascii(&self, f: &mut impl fmt::Write, path: &[Coord]) -> fmt::Result {
+        let marked: BTreeSet<&Coord> = path.iter().collect();
+        let interior = |coord: Coord| if marked.contains(&coord) { '*' } else { ' ' };
+
+        // first line contains upper walls
+        let first_line = "_".repeat(self.size.width * 2 - 1);
+
+        writeln!(f, " {}", first_line)?;
+
+        // a single column has no east walls nor posts, only cells and south walls
+        for j in 0..self.size.height {
+            let mut first_line = String::new();
+            let mut second_line = String::new();
+            for i in 0..self.size.width {
+                let cell = &self[(i, j)];
+                let is_last_column = i == self.size.width - 1;
+
+                // east wall line
+                first_line.push(interior((i, j)));
+                if !is_last_column {
+                    if cell.is_wall_enabled(&TileDirection::EAST) {
+                        first_line.push('#');
+                    } else {
+                        first_line.push(' ');
+                    }
+                }
+
+                // south wall line
+                if cell.is_wall_enabled(&TileDirection::SOUTH) {
+                    second_line.push('#');
+                } else {
+                    second_line.push(' ');
+                }
+                if !is_last_column {
+                    second_line.push('#');
+                }
+            }
+
+            writeln!(f, "|{}|", first_line)?;
+            if j < self.size.height - 1 {
+                writeln!(f, "|{}|", second_line)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// both panic with the coord and the maze size when out of bounds
+impl<T> Index<Coord> for Maze<T> {
+    type Output = Cell<T>;
+
+    fn index(&self, coord: Coord) -> &Cell<T> {
+        let size = self.size;
+        self.get_cell(coord).unwrap_or_else(|| {
+            panic!(
+                "coord {:?} is out of bounds for a {}x{} maze",
+                coord, size.width, size.height
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<Coord> for Maze<T> {
+    fn index_mut(&mut self, coord: Coord) -> &mut Cell<T> {
+        let size = self.size;
+        self.get_mut_cell(coord).unwrap_or_else(|| {
+            panic!(
+                "coord {:?} is out of bounds for a {}x{} maze",
+                coord, size.width, size.height
+            )
+        })
+    }
+}
+
+/// Mazes are equal when they have the same size, the same walls, openings included,
+/// and the same cell data.
+impl<T: PartialEq> PartialEq for Maze<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.size == other.size
+            && self.iter_cells().all(|cell| {
+                cell.data == other[cell.coord].data
+                    && ALL_TILE_DIRECTIONS.iter().all(|dir| {
+                        self.is_wall_enabled(cell.coord, dir)
+                            == other.is_wall_enabled(cell.coord, dir)
+                    })
+            })
+    }
+}
+
+impl<T: Eq> Eq for Maze<T> {}
+
+#[cfg(feature = "std")]
+impl<T> fmt::Display for Maze<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_ascii(f, &[])
+    }
+}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum ParseError {
+    Empty,
+    // first line isn't a space followed by an odd number of underscores
+    BadHeader,
+    // the last row has no line
+    MissingLine,
+    RaggedLine {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    UnexpectedChar {
+        line: usize,
+        column: usize,
+        found: char,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Empty => write!(f, "empty maze"),
+            ParseError::BadHeader => {
+                write!(f, "first line should be a space followed by underscores")
+            }
+            ParseError::MissingLine => write!(f, "expected two lines per row of cells"),
+            ParseError::RaggedLine {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {} has {} characters, expected {}",
+                line, found, expected
+            ),
+            ParseError::UnexpectedChar {
+                line,
+                column,
+                found,
+            } => write!(
+                f,
+                "unexpected character {:?} at line {}, column {}",
+                found, line, column
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum MazeError {
+    // outside the grid, or masked out
+    OutOfBounds(Coord),
+    ZeroDimension,
+    // wall grid sides aren't odd, or columns have different lengths
+    BadGridDimensions,
+    // outer border with no opening or more than two
+    BadOpenings,
+    // entrance or exit on a side that isn't part of the outer border
+    NotOuterWall(Coord, TileDirection),
+    // paired parameter slices of different lengths
+    LengthMismatch(usize, usize),
+}
+
+impl fmt::Display for MazeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MazeError::OutOfBounds(coord) => write!(f, "coord {:?} is out of bounds", coord),
+            MazeError::ZeroDimension => write!(f, "maze width and height must be at least 1"),
+            MazeError::BadGridDimensions => {
+                write!(f, "wall grid must be (2 * width + 1) x (2 * height + 1)")
+            }
+            MazeError::BadOpenings => write!(f, "outer border must have one or two openings"),
+            MazeError::NotOuterWall(coord, dir) => {
+                write!(f, "{:?} side of {:?} isn't an outer wall", dir, coord)
+            }
+            MazeError::LengthMismatch(a, b) => {
+                write!(
+                    f,
+                    "parameter lists have different lengths ({} and {})",
+                    a, b
+                )
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MazeError {}
+
+#[derive(Eq, PartialEq, Debug, Clone)]
+pub enum ConnectError {
+    OutOfBounds(Coord),
+    NotAdjacent(Coord, Coord),
+}
+
+impl fmt::Display for ConnectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConnectError::OutOfBounds(coord) => write!(f, "coord {:?} is out of bounds", coord),
+            ConnectError::NotAdjacent(a, b) => {
+                write!(f, "cells {:?} and {:?} aren't neighbors", a, b)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConnectError {}
+
+// union-find over cells, used to tell whether two cells are already connected
+struct DisjointSet {
+    parents: BTreeMap<Coord, Coord>,
+}
+
+impl DisjointSet {
+    fn new() -> Self {
+        Self {
+            parents: BTreeMap::new(),
+        }
+    }
+
+    fn find(&mut self, coord: Coord) -> Coord {
+        let parent = *self.parents.entry(coord).or_insert(coord);
+        if parent == coord {
+            return coord;
+        }
+
+        let root = self.find(parent);
+        // path compression
+        self.parents.insert(coord, root);
+        root
+    }
+
+    // returns false if both were already in the same set
+    fn union(&mut self, a: Coord, b: Coord) -> bool {
+        let (root_a, root_b) = (self.find(a), self.find(b));
+        if root_a == root_b {
+            return false;
+        }
+
+        self.parents.insert(root_a, root_b);
+        true
+    }
+}
+
+// spanning tree where every cell points to its parent, rooted at the origin
+struct OriginShift {
+    origin: Coord,
+    parents: Vec<Vec<Option<Coord>>>,
+}
+
+pub struct MazeGen<T = ()> {
+    pub maze: Maze<T>,
+    left_to_visit: BTreeSet<Coord>,
+    path_stack: Vec<Coord>,
+    // backtracker cursor, None when there is nothing left to carve
+    current: Option<Coord>,
+    last_dir: Option<TileDirection>,
+    rng: Pcg64,
+    origin_shift: Option<OriginShift>,
+    // cells left out by a mask, never visited nor carved
+    excluded: BTreeSet<Coord>,
+    last_seed: Option<u64>,
+    // seed of the run `generate_steps` completed, until another generation replaces it
+    finished_seed: Option<u64>,
+    visit_order: Vec<Coord>,
+}
+
+impl MazeGen {
+    pub fn new(size: &Size) -> Self {
+        MazeGen::new_with_topology(size, Topology::Planar)
+    }
+
+    pub fn new_with_topology(size: &Size, topology: Topology) -> Self {
+        MazeGen::from_maze(Maze::new_with_topology(size, topology))
+    }
+
+    /// Generator restricted to the cells where `mask` returns true, for non rectangular
+    /// shapes. The other cells keep all their walls. The active cells should form a
+    /// connected region. The binary tree, sidewinder, Eller, recursive division and
+    /// Origin Shift generators need the full grid and panic on a masked generator.
+    pub fn new_masked(size: &Size, mask: &dyn Fn(Coord) -> bool) -> Self {
+        let mut mazegen = MazeGen::new(size);
+        mazegen.excluded = Self::all_coords(size)
+            .into_iter()
+            .filter(|coord| !mask(*coord))
+            .collect();
+        mazegen.reset();
+
+        mazegen
+    }
+
+    /// One backtracker maze per `sizes[i]`, braided by `braids[i]`, e.g. for a campaign
+    /// of increasingly hard levels. Maze `i` is seeded from `base_seed` and `i` only, so
+    /// a level doesn't change when others are added or removed after it.
+    pub fn generate_pack(
+        base_seed: u64,
+        sizes: &[Size],
+        braids: &[f64],
+    ) -> Result<Vec<Maze>, MazeError> {
+        if sizes.len() != braids.len() {
+            return Err(MazeError::LengthMismatch(sizes.len(), braids.len()));
+        }
+
+        sizes
+            .iter()
+            .zip(braids.iter())
+            .enumerate()
+            .map(|(index, (size, braid))| {
+                // one PCG stream per level
+                let seed = Pcg64::new(base_seed as u128, index as u128).gen();
+                MazeBuilder::new()
+                    .size(*size)
+                    .seed(seed)
+                    .braid(*braid)
+                    .build()
+            })
+            .collect()
+    }
+}
+
+impl<T> MazeGen<T> {
+    /// Generator carving into `maze`, e.g. one built with `Maze::new_with_data`.
+    pub fn from_maze(maze: Maze<T>) -> Self {
+        Self {
+            left_to_visit: Self::all_coords(&maze.size),
+            maze,
+            path_stack: Vec::new(),
+            current: None,
+            last_dir: None,
+            rng: Pcg64::seed_from_u64(0),
+            origin_shift: None,
+            excluded: BTreeSet::new(),
+            last_seed: None,
+            finished_seed: None,
+            visit_order: Vec::new(),
+        }
+    }
+
+    // first cell that isn't masked out, in [x][y] order
+    fn start_coord(&self) -> Option<Coord> {
+        let size = self.maze.size;
+        (0..size.width)
+            .flat_map(|i| (0..size.height).map(move |j| (i, j)))
+            .find(|coord| !self.excluded.contains(coord))
+    }
+
+    // neighbors of `coord` that aren't masked out, visited or not
+    fn active_neighbors(&self, coord: Coord) -> Vec<(Coord, TileDirection)> {
+        self.maze
+            .get_neighbor_coords_and_dirs(coord)
+            .into_iter()
+            .filter(|(next, _)| !self.excluded.contains(next))
+            .collect()
+    }
+
+    // active cells reachable from `start` through the grid, walls ignored
+    fn active_region(&self, start: Coord) -> BTreeSet<Coord> {
+        let mut region = BTreeSet::new();
+        let mut stack = vec![start];
+        region.insert(start);
+        while let Some(coord) = stack.pop() {
+            for (next, _) in self.active_neighbors(coord) {
+                if region.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+
+        region
+    }
+
+    // for the generators working on whole rows or rectangular chambers
+    fn assert_unmasked(&self, generator: &str) {
+        assert!(
+            self.excluded.is_empty(),
+            "{} can't generate a masked maze",
+            generator
+        );
+    }
+
+    fn all_coords(size: &Size) -> BTreeSet<Coord> {
+        let mut coords = BTreeSet::new();
+        for i in 0..size.width {
+            for j in 0..size.height {
+                coords.insert((i, j));
+            }
+        }
+
+        coords
+    }
+
+    fn get_valid_neighbor_coords_and_dirs(&self, coord: Coord) -> Vec<(Coord, TileDirection)> {
+        self.maze
+            .neighbors_ordered(coord)
+            .into_iter()
+            .filter(|(coord, _)| self.left_to_visit.contains(coord))
+            .collect()
+    }
+
+    fn reset(&mut self) {
+        self.maze.enable_all_walls();
+        self.path_stack.clear();
+        self.left_to_visit = Self::all_coords(&self.maze.size)
+            .difference(&self.excluded)
+            .copied()
+            .collect();
+        self.current = None;
+        self.visit_order.clear();
+        self.origin_shift = None;
+        self.finished_seed = None;
+    }
+
+    fn begin_backtracker(&mut self) {
+        self.begin_backtracker_at(self.start_coord());
+    }
+
+    fn begin_backtracker_at(&mut self, start: Option<Coord>) {
+        self.current = start;
+        self.last_dir = None;
+        if let Some(start) = self.current {
+            self.left_to_visit.remove(&start);
+            self.visit_order.push(start);
+            self.update_backtracker_progress();
+        }
+    }
+
+    fn update_backtracker_progress(&mut self) {
+        if self.path_stack.is_empty() && self.left_to_visit.is_empty() {
+            self.current = None;
+        }
+    }
+
+    // one algo step: carve towards the chosen neighbor, or backtrack if there is none
+    fn apply_backtracker_step(&mut self, choice: Option<(Coord, TileDirection)>) {
+        let coord = self.current.unwrap();
+
+        match choice {
+            None => {
+                self.current = self.path_stack.pop();
+                self.last_dir = None;
+            }
+            Some((next_coord, dir)) => {
+                // remove wall between current and next cell
+                self.maze.disable_wall(coord, &dir);
+
+                self.path_stack.push(coord);
+                self.current = Some(next_coord);
+                self.last_dir = Some(dir);
+                self.left_to_visit.remove(&next_coord);
+                self.visit_order.push(next_coord);
+            }
+        }
+
+        self.update_backtracker_progress();
+    }
+
+    // runs the backtracker from the first active cell, asking `keep_going` before every step;
+    // `pick` chooses among the unvisited neighbors given the direction of the move
+    // into the current cell (None right after backtracking);
+    // returns false if it was stopped before every cell got visited
+    fn carve_backtracker(
+        &mut self,
+        mut pick: impl FnMut(
+            Vec<(Coord, TileDirection)>,
+            Option<TileDirection>,
+        ) -> Option<(Coord, TileDirection)>,
+        mut keep_going: impl FnMut() -> bool,
+    ) -> bool {
+        self.begin_backtracker();
+
+        while let Some(coord) = self.current {
+            if !keep_going() {
+                return false;
+            }
+
+            let choice = pick(
+                self.get_valid_neighbor_coords_and_dirs(coord),
+                self.last_dir,
+            );
+            self.apply_backtracker_step(choice);
+        }
+
+        true
+    }
+
+    /// Runs the backtracker from a random seed and returns it, so the maze can be
+    /// reproduced with `generate_seeded`.
+    #[cfg(feature = "std")]
+    pub fn generate(&mut self) -> u64 {
+        let seed = Pcg64::from_entropy().gen();
+        self.generate_seeded(seed);
+        seed
+    }
+
+    /// Cells in the order the latest backtracker run first reached them, starting with
+    /// the start cell. Empty after the other algorithms.
+    pub fn visit_order(&self) -> &[Coord] {
+        &self.visit_order
+    }
+
+    /// Seed of the latest backtracker run, if any.
+    pub fn last_seed(&self) -> Option<u64> {
+        self.last_seed
+    }
+
+    /// Backtracker run that only depends on `seed`: neighbors are tried in
+    /// `Maze::neighbors_ordered` order and the RNG is `Pcg64`, so seed 0 on a 4x4 grid
+    /// always gives
+    ///
+    /// ```text
+    ///  _______
+    /// |     # |
+    /// |#### # |
+    /// | #   # |
+    /// | # ### |
+    /// | # #   |
+    /// | # ### |
+    /// |       |
+    /// ```
+    pub fn generate_seeded(&mut self, seed: u64) {
+        self.start(seed);
+        while self.step() {}
+    }
+
+    /// Seeded backtracker carving from `start` instead of the first cell.
+    pub fn generate_from(&mut self, seed: u64, start: Coord) -> Result<(), MazeError> {
+        if !self.maze.is_valid_coord(&start) || self.excluded.contains(&start) {
+            return Err(MazeError::OutOfBounds(start));
+        }
+
+        self.reset();
+        self.rng = Pcg64::seed_from_u64(seed);
+        self.last_seed = Some(seed);
+        self.begin_backtracker_at(Some(start));
+        while self.step() {}
+
+        Ok(())
+    }
+
+    /// Resets the maze and prepares a seeded backtracker run driven by `step`.
+    pub fn start(&mut self, seed: u64) {
+        self.reset();
+        self.rng = Pcg64::seed_from_u64(seed);
+        self.last_seed = Some(seed);
+        self.begin_backtracker();
+    }
+
+    /// Performs exactly one backtracker step (carve or backtrack) of the run prepared by
+    /// `start`. Returns false once generation is complete, including before `start`.
+    pub fn step(&mut self) -> bool {
+        let coord = match self.current {
+            None => return false,
+            Some(coord) => coord,
+        };
+
+        let choice = self.choose_backtracker_step(coord);
+        self.apply_backtracker_step(choice);
+
+        self.current.is_some()
+    }
+
+    /// Runs at most `max_steps` backtracker steps and returns whether the maze is done.
+    /// Continues the run in progress if it was started with the same `seed` (by an
+    /// earlier call or by `start`), otherwise starts a new one. Once a run is finished,
+    /// further calls with its seed return true and keep the maze.
+    pub fn generate_steps(&mut self, seed: u64, max_steps: usize) -> bool {
+        if self.finished_seed == Some(seed) {
+            return true;
+        }
+        if self.current.is_none() || self.last_seed != Some(seed) {
+            self.start(seed);
+        }
+
+        for _ in 0..max_steps {
+            if !self.step() {
+                break;
+            }
+        }
+
+        if self.current.is_some() {
+            return false;
+        }
+        self.finished_seed = Some(seed);
+        true
+    }
+
+    fn choose_backtracker_step(&mut self, coord: Coord) -> Option<(Coord, TileDirection)> {
+        self.get_valid_neighbor_coords_and_dirs(coord)
+            .into_iter()
+            .choose(&mut self.rng)
+    }
+
+    /// Backtracker run that also adds loops while carving: on reaching a new cell, with
+    /// probability `loop_density`, the wall toward one of its already visited neighbors
+    /// is carved too. The loops are drawn from their own RNG, so the underlying tree is
+    /// the `generate_seeded` one and a density of 0.0 gives exactly that perfect maze.
+    pub fn generate_with_loops(&mut self, seed: u64, loop_density: f64) {
+        let loop_density = loop_density.clamp(0.0, 1.0);
+        let mut loop_rng = Pcg64::seed_from_u64(!seed);
+
+        self.start(seed);
+        let mut reached = 0;
+        while let Some(coord) = self.current {
+            if self.visit_order.len() > reached {
+                reached = self.visit_order.len();
+
+                if loop_density > 0.0 && loop_rng.gen_bool(loop_density) {
+                    let visited = self
+                        .maze
+                        .neighbors_ordered(coord)
+                        .into_iter()
+                        .filter(|(next, dir)| {
+                            !self.left_to_visit.contains(next)
+                                && !self.excluded.contains(next)
+                                && self.maze.is_wall_enabled(coord, dir)
+                        })
+                        .choose(&mut loop_rng);
+                    if let Some((_, dir)) = visited {
+                        self.maze.disable_wall(coord, &dir);
+                    }
+                }
+            }
+
+            self.step();
+        }
+    }
+
+    /// Seeded backtracker followed by `Maze::braid` zone by zone: the dead ends inside
+    /// each `Rect` are braided with its ratio, the first matching zone winning where
+    /// they overlap. Dead ends outside every zone are kept.
+    pub fn generate_zoned(&mut self, seed: u64, zones: Vec<(Rect, f64)>) {
+        const DEFAULT_BRAID: f64 = 0.0;
+
+        self.generate_seeded(seed);
+
+        let mut rng = Pcg64::seed_from_u64(!seed);
+        let mut by_zone = vec![Vec::new(); zones.len()];
+        let mut outside = Vec::new();
+        for coord in self.maze.dead_ends() {
+            match zones.iter().position(|(rect, _)| rect.contains(coord)) {
+                Some(zone) => by_zone[zone].push(coord),
+                None => outside.push(coord),
+            }
+        }
+
+        for (dead_ends, (_, ratio)) in by_zone.into_iter().zip(zones.iter()) {
+            self.maze
+                .braid_some(&mut rng, dead_ends, *ratio, &self.excluded);
+        }
+        self.maze
+            .braid_some(&mut rng, outside, DEFAULT_BRAID, &self.excluded);
+    }
+
+    /// Same maze as `generate_seeded`, also returning every visit, carve and backtrack
+    /// in the order they happened.
+    pub fn generate_recorded(&mut self, seed: u64) -> Vec<GenEvent> {
+        let mut events = Vec::new();
+        self.start(seed);
+        if let Some(coord) = self.current {
+            events.push(GenEvent::Visit { coord });
+        }
+
+        while let Some(coord) = self.current {
+            let choice = self.choose_backtracker_step(coord);
+            match choice {
+                Some((next, _)) => {
+                    events.push(GenEvent::Carve {
+                        from: coord,
+                        to: next,
+                    });
+                    events.push(GenEvent::Visit { coord: next });
+                }
+                None => {
+                    if let Some(&to) = self.path_stack.last() {
+                        events.push(GenEvent::Backtrack { to });
+                    }
+                }
+            }
+            self.apply_backtracker_step(choice);
+        }
+
+        events
+    }
+
+    /// Generates drawing all randomness from the caller's `rng`.
+    pub fn generate_with_rng<R: Rng>(&mut self, rng: &mut R) {
+        self.reset();
+        self.carve_backtracker(|candidates, _| candidates.into_iter().choose(rng), || true);
+    }
+
+    /// Backtracker where `river` (0.0 to 1.0) is the probability of carving straight
+    /// ahead whenever possible: high values give long corridors, low values turn often.
+    pub fn generate_river(&mut self, seed: u64, river: f64) {
+        self.reset();
+
+        let river = river.clamp(0.0, 1.0);
+        let mut rng = Pcg64::seed_from_u64(seed);
+        self.carve_backtracker(
+            |candidates, last_dir| {
+                let (straight, turns): (Vec<_>, Vec<_>) = candidates
+                    .into_iter()
+                    .partition(|(_, dir)| Some(*dir) == last_dir);
+
+                match straight.into_iter().next() {
+                    Some(ahead) if turns.is_empty() || rng.gen_bool(river) => Some(ahead),
+                    _ => turns.into_iter().choose(&mut rng),
+                }
+            },
+            || true,
+        );
+    }
+
+    /// Randomized Prim's: grows the maze from (0, 0) by carving a random frontier wall
+    /// between a visited and an unvisited cell, giving short, bushy branches.
+    pub fn generate_prim(&mut self, seed: u64) {
+        self.reset();
+
+        let mut rng = Pcg64::seed_from_u64(seed);
+
+        let start = match self.start_coord() {
+            None => return,
+            Some(start) => start,
+        };
+        self.left_to_visit.remove(&start);
+        let mut frontier: Vec<(Coord, Coord, TileDirection)> = self
+            .get_valid_neighbor_coords_and_dirs(start)
+            .into_iter()
+            .map(|(next, dir)| (start, next, dir))
+            .collect();
+
+        while !frontier.is_empty() {
+            let (coord, next, dir) = frontier.swap_remove(rng.gen_range(0..frontier.len()));
+            if !self.left_to_visit.remove(&next) {
+                // both sides got visited since this wall was added
+                continue;
+            }
+
+            self.maze.disable_wall(coord, &dir);
+            frontier.extend(
+                self.get_valid_neighbor_coords_and_dirs(next)
+                    .into_iter()
+                    .map(|(neighbor, dir)| (next, neighbor, dir)),
+            );
+        }
+    }
+
+    /// Kruskal's: goes through every interior wall in random order and carves it when
+    /// the cells on both sides aren't connected yet.
+    pub fn generate_kruskal(&mut self, seed: u64) {
+        self.reset();
+
+        let mut rng = Pcg64::seed_from_u64(seed);
+
+        let size = self.maze.size;
+        let mut walls = Vec::new();
+        for i in 0..size.width {
+            for j in 0..size.height {
+                if i + 1 < size.width {
+                    walls.push(((i, j), (i + 1, j), TileDirection::EAST));
+                }
+                if j + 1 < size.height {
+                    walls.push(((i, j), (i, j + 1), TileDirection::SOUTH));
+                }
+            }
+        }
+        walls.shuffle(&mut rng);
+
+        let mut sets = DisjointSet::new();
+        for (coord, next, dir) in walls {
+            if self.excluded.contains(&coord) || self.excluded.contains(&next) {
+                continue;
+            }
+            if sets.union(coord, next) {
+                self.maze.disable_wall(coord, &dir);
+            }
+        }
+
+        self.left_to_visit.clear();
+    }
+
+    /// Wilson's: loop-erased random walks from unvisited cells until they hit the maze,
+    /// producing a uniformly random spanning tree.
+    pub fn generate_wilson(&mut self, seed: u64) {
+        self.reset();
+
+        let mut rng = Pcg64::seed_from_u64(seed);
+
+        let size = self.maze.size;
+        let active_coords: Vec<Coord> = (0..size.width)
+            .flat_map(|i| (0..size.height).map(move |j| (i, j)))
+            .filter(|coord| !self.excluded.contains(coord))
+            .collect();
+
+        let first = match active_coords.choose(&mut rng) {
+            None => return,
+            Some(first) => *first,
+        };
+        self.left_to_visit.remove(&first);
+        // walks from a part of the mask cut off from `first` would never end
+        let region = self.active_region(first);
+
+        for walk_start in active_coords {
+            if !self.left_to_visit.contains(&walk_start) || !region.contains(&walk_start) {
+                continue;
+            }
+
+            // only the last exit taken from each cell is kept, which erases loops
+            let mut exits: BTreeMap<Coord, (Coord, TileDirection)> = BTreeMap::new();
+            let mut coord = walk_start;
+            while self.left_to_visit.contains(&coord) {
+                let (next, dir) = self
+                    .active_neighbors(coord)
+                    .into_iter()
+                    .choose(&mut rng)
+                    .unwrap();
+                exits.insert(coord, (next, dir));
+                coord = next;
+            }
+
+            let mut coord = walk_start;
+            while self.left_to_visit.remove(&coord) {
+                let (next, dir) = exits[&coord];
+                self.maze.disable_wall(coord, &dir);
+                coord = next;
+            }
+        }
+    }
+
+    /// Aldous-Broder: a pure random walk from (0, 0) that carves whenever it steps into
+    /// an unvisited cell, until every cell has been visited. Uniform like Wilson's, but slower.
+    pub fn generate_aldous_broder(&mut self, seed: u64) {
+        self.reset();
+
+        let mut rng = Pcg64::seed_from_u64(seed);
+
+        let mut coord = match self.start_coord() {
+            None => return,
+            Some(start) => start,
+        };
+        self.left_to_visit.remove(&coord);
+        // parts of the mask cut off from the start can't be walked to
+        let mut remaining = self.active_region(coord).len() - 1;
+
+        while remaining > 0 {
+            let (next, dir) = self
+                .active_neighbors(coord)
+                .into_iter()
+                .choose(&mut rng)
+                .unwrap();
+
+            if self.left_to_visit.remove(&next) {
+                self.maze.disable_wall(coord, &dir);
+                remaining -= 1;
+            }
+            coord = next;
+        }
+    }
+
+    /// Binary tree: every cell carves either north or east in a single pass. Border cells
+    /// only carve the side that's available, leaving an open top row and right column.
+    pub fn generate_binary_tree(&mut self, seed: u64) {
+        self.assert_unmasked("generate_binary_tree");
+        self.reset();
+
+        let mut rng = Pcg64::seed_from_u64(seed);
+
+        for i in 0..self.maze.size.width {
+            for j in 0..self.maze.size.height {
+                let coord = (i, j);
+                let dir = [TileDirection::NORTH, TileDirection::EAST]
+                    .iter()
+                    .filter(|dir| !self.maze.is_edge_wall(coord, dir))
+                    .choose(&mut rng);
+
+                if let Some(dir) = dir {
+                    self.maze.disable_wall(coord, dir);
+                }
+            }
+        }
+
+        self.left_to_visit.clear();
+    }
+
+    /// Sidewinder: row by row, cells are grouped in runs extended east until a random
+    /// decision closes the run by carving north from one of its cells. The top row is a
+    /// single corridor.
+    pub fn generate_sidewinder(&mut self, seed: u64) {
+        self.assert_unmasked("generate_sidewinder");
+        self.reset();
+
+        let mut rng = Pcg64::seed_from_u64(seed);
+
+        let size = self.maze.size;
+        for j in 0..size.height {
+            let mut run = Vec::new();
+            for i in 0..size.width {
+                run.push(i);
+
+                let at_east_edge = i == size.width - 1;
+                if j == 0 {
+                    if !at_east_edge {
+                        self.maze.disable_wall((i, j), &TileDirection::EAST);
+                    }
+                } else if at_east_edge || rng.gen_bool(0.5) {
+                    let carved = *run.choose(&mut rng).unwrap();
+                    self.maze.disable_wall((carved, j), &TileDirection::NORTH);
+                    run.clear();
+                } else {
+                    self.maze.disable_wall((i, j), &TileDirection::EAST);
+                }
+            }
+        }
+
+        self.left_to_visit.clear();
+    }
+
+    /// Eller's: carves one row at a time, only keeping the set membership of the current
+    /// row. Adjacent cells of different sets are randomly joined, every set carves at
+    /// least one passage south, and the last row joins all remaining sets.
+    pub fn generate_eller(&mut self, seed: u64) {
+        self.assert_unmasked("generate_eller");
+        self.reset();
+
+        let mut rng = Pcg64::seed_from_u64(seed);
+
+        let size = self.maze.size;
+        let mut row_sets: Vec<Option<usize>> = vec![None; size.width];
+        let mut next_set = 0;
+
+        for j in 0..size.height {
+            let last_row = j == size.height - 1;
+
+            for set in row_sets.iter_mut().filter(|set| set.is_none()) {
+                *set = Some(next_set);
+                next_set += 1;
+            }
+
+            // join neighbors in different sets, always on the last row
+            for i in 0..size.width - 1 {
+                let (set, next) = (row_sets[i], row_sets[i + 1]);
+                if set != next && (last_row || rng.gen_bool(0.5)) {
+                    self.maze.disable_wall((i, j), &TileDirection::EAST);
+                    for other in row_sets.iter_mut().filter(|other| **other == next) {
+                        *other = set;
+                    }
+                }
+            }
+
+            if last_row {
+                break;
+            }
+
+            // at least one passage south per set, cells not carved south start a new set
+            let mut members: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+            for (i, set) in row_sets.iter().enumerate() {
+                members.entry(set.unwrap()).or_default().push(i);
+            }
+
+            let mut next_row_sets = vec![None; size.width];
+            for (set, cells) in members {
+                let forced = *cells.choose(&mut rng).unwrap();
+                for i in cells {
+                    if i == forced || rng.gen_bool(0.5) {
+                        self.maze.disable_wall((i, j), &TileDirection::SOUTH);
+                        next_row_sets[i] = Some(set);
+                    }
+                }
+            }
+            row_sets = next_row_sets;
+        }
+
+        self.left_to_visit.clear();
+    }
+
+    /// Recursive division: starts from an open grid and splits every chamber with a wall
+    /// that has a single random gap, until chambers are one cell wide or tall. On a
+    /// toroidal maze the seams stay closed, the grid being divided as if it were planar.
+    pub fn generate_recursive_division(&mut self, seed: u64) {
+        self.assert_unmasked("generate_recursive_division");
+        self.reset();
+        self.maze.disable_all_walls();
+        self.left_to_visit.clear();
+
+        let size = self.maze.size;
+        if self.maze.wraps_horizontally() {
+            for j in 0..size.height {
+                self.maze
+                    .enable_wall((size.width - 1, j), &TileDirection::EAST);
+            }
+        }
+        if self.maze.wraps_vertically() {
+            for i in 0..size.width {
+                self.maze
+                    .enable_wall((i, size.height - 1), &TileDirection::SOUTH);
+            }
+        }
+
+        let mut rng = Pcg64::seed_from_u64(seed);
+
+        // chambers as (x, y, width, height)
+        let mut chambers = vec![(0, 0, size.width, size.height)];
+        while let Some((x, y, width, height)) = chambers.pop() {
+            if width < 2 || height < 2 {
+                continue;
+            }
+
+            let horizontal = match width.cmp(&height) {
+                Ordering::Less => true,
+                Ordering::Greater => false,
+                Ordering::Equal => rng.gen_bool(0.5),
+            };
+
+            if horizontal {
+                // wall along the south side of row `y + split - 1`
+                let split = rng.gen_range(1..height);
+                let gap = rng.gen_range(x..x + width);
+                for i in (x..x + width).filter(|i| *i != gap) {
+                    self.maze
+                        .enable_wall((i, y + split - 1), &TileDirection::SOUTH);
+                }
+                chambers.push((x, y, width, split));
+                chambers.push((x, y + split, width, height - split));
+            } else {
+                // wall along the east side of column `x + split - 1`
+                let split = rng.gen_range(1..width);
+                let gap = rng.gen_range(y..y + height);
+                for j in (y..y + height).filter(|j| *j != gap) {
+                    self.maze
+                        .enable_wall((x + split - 1, j), &TileDirection::EAST);
+                }
+                chambers.push((x, y, split, height));
+                chambers.push((x + split, y, width - split, height));
+            }
+        }
+    }
+
+    /// Hunt-and-kill: random walk into unvisited cells; when stuck, scan row by row for
+    /// the first unvisited cell next to a visited one, connect it and walk from there.
+    pub fn generate_hunt_and_kill(&mut self, seed: u64) {
+        self.reset();
+
+        let mut rng = Pcg64::seed_from_u64(seed);
+
+        let mut coord = match self.start_coord() {
+            None => return,
+            Some(start) => start,
+        };
+        self.left_to_visit.remove(&coord);
+
+        loop {
+            // kill: walk while possible
+            while let Some((next, dir)) = self
+                .get_valid_neighbor_coords_and_dirs(coord)
+                .into_iter()
+                .choose(&mut rng)
+            {
+                self.maze.disable_wall(coord, &dir);
+                self.left_to_visit.remove(&next);
+                coord = next;
+            }
+
+            // hunt: first unvisited cell with a visited neighbor
+            let size = self.maze.size;
+            let hunted = (0..size.height)
+                .flat_map(|j| (0..size.width).map(move |i| (i, j)))
+                .filter(|coord| self.left_to_visit.contains(coord))
+                .find_map(|coord| {
+                    self.active_neighbors(coord)
+                        .into_iter()
+                        .filter(|(neighbor, _)| !self.left_to_visit.contains(neighbor))
+                        .choose(&mut rng)
+                        .map(|(_, dir)| (coord, dir))
+                });
+
+            match hunted {
+                None => break,
+                Some((next, dir)) => {
+                    self.maze.disable_wall(next, &dir);
+                    self.left_to_visit.remove(&next);
+                    coord = next;
+                }
+            }
+        }
+    }
+
+    /// Growing tree: keeps a list of active cells, carves from one chosen by `strategy`
+    /// and drops cells with no unvisited neighbor left.
+    pub fn generate_growing_tree(&mut self, seed: u64, strategy: CellSelection) {
+        self.reset();
+
+        let mut rng = Pcg64::seed_from_u64(seed);
+
+        let start = match self.start_coord() {
+            None => return,
+            Some(start) => start,
+        };
+        self.left_to_visit.remove(&start);
+        let mut active = VecDeque::new();
+        active.push_back(start);
+
+        while !active.is_empty() {
+            let index = match strategy {
+                CellSelection::Newest => active.len() - 1,
+                CellSelection::Random => rng.gen_range(0..active.len()),
+                CellSelection::Oldest => 0,
+            };
+            let coord = active[index];
+
+            match self
+                .get_valid_neighbor_coords_and_dirs(coord)
+                .into_iter()
+                .choose(&mut rng)
+            {
+                None => {
+                    active.swap_remove_back(index);
+                }
+                Some((next, dir)) => {
+                    self.maze.disable_wall(coord, &dir);
+                    self.left_to_visit.remove(&next);
+                    active.push_back(next);
+                }
+            }
+        }
+    }
+
+    /// Prim-style growth where frontier walls are carved in order of
+    /// `weight(from, to)` scaled by a random factor: lower weights get carved earlier,
+    /// so cheap regions fill in first with denser, shorter routes.
+    pub fn generate_weighted<F: Fn(Coord, Coord) -> f64>(&mut self, seed: u64, weight: F) {
+        self.reset();
+
+        let mut rng = Pcg64::seed_from_u64(seed);
+
+        let start = match self.start_coord() {
+            None => return,
+            Some(start) => start,
+        };
+        self.left_to_visit.remove(&start);
+
+        // keys are non-negative floats, whose bit patterns sort like the values
+        let mut walls = Vec::new();
+        let mut frontier = BinaryHeap::new();
+        let mut newly_visited = Some(start);
+
+        loop {
+            if let Some(coord) = newly_visited.take() {
+                for (next, dir) in self.get_valid_neighbor_coords_and_dirs(coord) {
+                    let key = weight(coord, next).max(0.0) * rng.gen::<f64>();
+                    frontier.push(Reverse((key.to_bits(), walls.len())));
+                    walls.push((coord, next, dir));
+                }
+            }
+
+            let (coord, next, dir) = match frontier.pop() {
+                None => break,
+                Some(Reverse((_, index))) => walls[index],
+            };
+            if !self.left_to_visit.remove(&next) {
+                continue;
+            }
+
+            self.maze.disable_wall(coord, &dir);
+            newly_visited = Some(next);
+        }
+    }
+
+    /// Generates `count` disconnected perfect mazes: regions grow from random seed cells
+    /// at the same time, each only carving into unclaimed cells, so every region is a
+    /// contiguous tree with no passage to any other. `count` is capped to the cell count.
+    pub fn generate_regions(&mut self, seed: u64, count: usize) {
+        self.reset();
+
+        let mut rng = Pcg64::seed_from_u64(seed);
+
+        let size = self.maze.size;
+        let mut active = (0..size.width)
+            .flat_map(|i| (0..size.height).map(move |j| (i, j)))
+            .filter(|coord| !self.excluded.contains(coord))
+            .choose_multiple(&mut rng, count);
+        for coord in &active {
+            self.left_to_visit.remove(coord);
+        }
+
+        while !active.is_empty() {
+            let index = rng.gen_range(0..active.len());
+            let coord = active[index];
+
+            match self
+                .get_valid_neighbor_coords_and_dirs(coord)
+                .into_iter()
+                .choose(&mut rng)
+            {
+                None => {
+                    active.swap_remove(index);
+                }
+                Some((next_coord, dir)) => {
+                    self.maze.disable_wall(coord, &dir);
+                    self.left_to_visit.remove(&next_coord);
+                    active.push(next_coord);
+                }
+            }
+        }
+    }
+
+    /// Fills in a hand-drawn partial maze: `base`'s open passages are kept as pre-carved
+    /// corridors and the backtracker connects everything else into a single maze.
+    /// The result is perfect as long as `base` has no loops. `base`'s topology, border
+    /// and openings are kept as well.
+    pub fn complete(&mut self, base: &Maze<T>, seed: u64)
+    where
+        T: Clone,
+    {
+        // reset, but with `base`'s walls instead of closing them all
+        self.maze = base.clone();
+        self.path_stack.clear();
+        self.current = None;
+        self.visit_order.clear();
+        self.origin_shift = None;
+        self.finished_seed = None;
+        self.left_to_visit = Self::all_coords(&base.size)
+            .difference(&self.excluded)
+            .copied()
+            .collect();
+
+        let mut rng = Pcg64::seed_from_u64(seed);
+        match self.start_coord() {
+            None => return,
+            Some(start) => self.claim_component(start),
+        }
+
+        while let Some(&coord) = self.path_stack.last() {
+            match self
+                .get_valid_neighbor_coords_and_dirs(coord)
+                .into_iter()
+                .choose(&mut rng)
+            {
+                None => {
+                    self.path_stack.pop();
+                }
+                Some((next_coord, dir)) => {
+                    self.maze.disable_wall(coord, &dir);
+                    self.claim_component(next_coord);
+                }
+            }
+        }
+    }
+
+    // marks every cell already connected to `coord` as visited and pushes them
+    // on the stack, `coord` last so carving carries on from there
+    fn claim_component(&mut self, coord: Coord) {
+        for (_, cell) in self.maze.tree_edges(coord) {
+            if self.excluded.contains(&cell) {
+                continue;
+            }
+            self.left_to_visit.remove(&cell);
+            self.path_stack.push(cell);
+        }
+        self.left_to_visit.remove(&coord);
+        self.path_stack.push(coord);
+    }
+
+    /// Starts an Origin Shift maze: a perfect maze kept as a spanning tree rooted at
+    /// an origin cell that `origin_shift_step` moves around.
+    pub fn origin_shift_init(&mut self, seed: u64) {
+        self.assert_unmasked("origin_shift_init");
+        self.reset();
+        self.left_to_visit.clear();
+        let size = self.maze.size;
+
+        // every cell points east, the last column points south,
+        // which leaves the bottom-right cell as the root
+        let mut parents = vec![vec![None; size.height]; size.width];
+        for (i, column) in parents.iter_mut().enumerate() {
+            for (j, parent) in column.iter_mut().enumerate() {
+                let dir = if i < size.width - 1 {
+                    TileDirection::EAST
+                } else if j < size.height - 1 {
+                    TileDirection::SOUTH
+                } else {
+                    continue;
+                };
+
+                self.maze.disable_wall((i, j), &dir);
+                *parent = self
+                    .maze
+                    .get_neighbor_coords_and_dirs((i, j))
+                    .into_iter()
+                    .find(|(_, neighbor_dir)| *neighbor_dir == dir)
+                    .map(|(coord, _)| coord);
+            }
+        }
+
+        self.origin_shift = Some(OriginShift {
+            origin: (size.width - 1, size.height - 1),
+            parents,
+        });
+
+        // shuffle away the initial comb pattern
+        self.rng = Pcg64::seed_from_u64(seed);
+        for _ in 0..size.width * size.height * 10 {
+            self.origin_shift_move();
+        }
+    }
+
+    /// Moves the origin to a random neighbor and flips the edge between them. The
+    /// maze stays perfect after every step. Steps continue the random stream seeded
+    /// by `origin_shift_init`; `seed` is only used to initialize when that hasn't
+    /// happened yet, or when another generator has run since.
+    pub fn origin_shift_step(&mut self, seed: u64) {
+        if self.origin_shift.is_none() {
+            self.origin_shift_init(seed);
+        }
+
+        self.origin_shift_move();
+    }
+
+    fn origin_shift_move(&mut self) {
+        let state = self.origin_shift.as_mut().unwrap();
+        let origin = state.origin;
+
+        let (next, dir) = match self
+            .maze
+            .get_neighbor_coords_and_dirs(origin)
+            .into_iter()
+            .choose(&mut self.rng)
+        {
+            None => return,
+            Some(neighbor) => neighbor,
+        };
+
+        // the new origin loses its outgoing edge, the old origin points to it
+        if let Some(old_parent) = state.parents[next.0][next.1].take() {
+            let old_dir = self.maze.get_dir_between(next, old_parent).unwrap();
+            self.maze.enable_wall(next, &old_dir);
+        }
+        state.parents[origin.0][origin.1] = Some(next);
+        state.origin = next;
+
+        self.maze.disable_wall(origin, &dir);
+    }
+
+    /// Generates with a self-imposed deadline, checked every few steps. Returns false
+    /// if the budget ran out, leaving the cells carved so far as a valid partial maze
+    /// and the run in progress: `step`, or `generate_steps` with the same `seed`,
+    /// carries it on to the `generate_seeded` maze.
+    #[cfg(feature = "std")]
+    pub fn generate_budgeted(&mut self, seed: u64, budget: Duration) -> bool {
+        const STEPS_BETWEEN_CHECKS: usize = 64;
+
+        self.start(seed);
+
+        let began = Instant::now();
+        let mut steps = 0;
+        while self.current.is_some() {
+            steps += 1;
+            if steps % STEPS_BETWEEN_CHECKS == 0 && began.elapsed() >= budget {
+                return false;
+            }
+            self.step();
+        }
+
+        true
+    }
+}
+
+// entropy comes from the OS, so without `std` unseeded builds use seed 0
+#[cfg(feature = "std")]
+fn entropy_seed() -> u64 {
+    Pcg64::from_entropy().gen()
+}
+
+#[cfg(not(feature = "std"))]
+fn entropy_seed() -> u64 {
+    0
+}
+
+/// One-call configuration of a maze:
+/// `MazeBuilder::new().size(size).algorithm(Algorithm::Prim).seed(7).braid(0.5).build()`.
+/// Defaults to an entropy-seeded planar backtracker maze with no braiding (seed 0
+/// without the `std` feature); the size has to be set.
+#[derive(Debug, Clone)]
+pub struct MazeBuilder {
+    size: Size,
+    seed: Option<u64>,
+    algorithm: Algorithm,
+    braid: f64,
+    topology: Topology,
+    entrance: Option<(Coord, TileDirection)>,
+    exit: Option<(Coord, TileDirection)>,
+}
+
+impl Default for MazeBuilder {
+    fn default() -> Self {
+        MazeBuilder::new()
+    }
+}
+
+impl MazeBuilder {
+    pub fn new() -> Self {
         Self {
-            maze: Maze::new(size),
-            left_to_visit,
-            path_stack: Vec::new(),
+            size: Size {
+                width: 0,
+                height: 0,
+            },
+            seed: None,
+            algorithm: Algorithm::Backtracker,
+            braid: 0.0,
+            topology: Topology::Planar,
+            entrance: None,
+            exit: None,
         }
     }
 
-    fn get_valid_neighbor_coords_and_dirs(&self, coord: Coord) -> Vec<(Coord, TileDirection)> {
-        self.maze
-            .get_neighbor_coords_and_dirs(coord)
+    pub fn size(mut self, size: Size) -> Self {
+        self.size = size;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// Ratio of dead ends to remove after generation, see `Maze::braid`.
+    pub fn braid(mut self, ratio: f64) -> Self {
+        self.braid = ratio;
+        self
+    }
+
+    pub fn topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    /// Outer wall to carve as the entrance, see `Maze::set_entrance`.
+    pub fn entrance(mut self, coord: Coord, direction: TileDirection) -> Self {
+        self.entrance = Some((coord, direction));
+        self
+    }
+
+    /// Outer wall to carve as the exit, see `Maze::set_exit`.
+    pub fn exit(mut self, coord: Coord, direction: TileDirection) -> Self {
+        self.exit = Some((coord, direction));
+        self
+    }
+
+    /// Fails on an unset or empty size, or on an entrance or exit that isn't an outer
+    /// wall of the maze.
+    pub fn build(&self) -> Result<Maze, MazeError> {
+        if self.size.width == 0 || self.size.height == 0 {
+            return Err(MazeError::ZeroDimension);
+        }
+
+        let mut mazegen = MazeGen::new_with_topology(&self.size, self.topology);
+        for (coord, dir) in self.entrance.iter().chain(self.exit.iter()) {
+            if !mazegen.maze.is_valid_coord(coord) {
+                return Err(MazeError::OutOfBounds(*coord));
+            }
+            if !mazegen.maze.is_edge_wall(*coord, dir) {
+                return Err(MazeError::NotOuterWall(*coord, *dir));
+            }
+        }
+
+        let seed = self.seed.unwrap_or_else(entropy_seed);
+        match self.algorithm {
+            Algorithm::Backtracker => mazegen.generate_seeded(seed),
+            Algorithm::Prim => mazegen.generate_prim(seed),
+            Algorithm::Kruskal => mazegen.generate_kruskal(seed),
+            Algorithm::Wilson => mazegen.generate_wilson(seed),
+            Algorithm::AldousBroder => mazegen.generate_aldous_broder(seed),
+            Algorithm::BinaryTree => mazegen.generate_binary_tree(seed),
+            Algorithm::Sidewinder => mazegen.generate_sidewinder(seed),
+            Algorithm::Eller => mazegen.generate_eller(seed),
+            Algorithm::RecursiveDivision => mazegen.generate_recursive_division(seed),
+            Algorithm::HuntAndKill => mazegen.generate_hunt_and_kill(seed),
+            Algorithm::GrowingTree(strategy) => mazegen.generate_growing_tree(seed, strategy),
+        }
+
+        let mut maze = mazegen.maze;
+        if self.braid > 0.0 {
+            maze.braid(seed, self.braid);
+        }
+        if let Some((coord, dir)) = self.entrance {
+            maze.set_entrance(coord, &dir);
+        }
+        if let Some((coord, dir)) = self.exit {
+            maze.set_exit(coord, &dir);
+        }
+
+        Ok(maze)
+    }
+}
+
+/// Stacked `Maze` layers joined by stairs between vertically adjacent cells.
+pub struct Maze3D {
+    pub size: Size,
+    pub depth: usize,
+    layers: Vec<Maze>,
+    // (x, y, z) means a stair from layer z up to layer z + 1
+    stairs: BTreeSet<Coord3D>,
+}
+
+impl Maze3D {
+    pub fn new(width: usize, height: usize, depth: usize) -> Self {
+        let size = Size { width, height };
+        Self {
+            size,
+            depth,
+            layers: (0..depth).map(|_| Maze::new(&size)).collect(),
+            stairs: BTreeSet::new(),
+        }
+    }
+
+    pub fn layer(&self, z: usize) -> &Maze {
+        &self.layers[z]
+    }
+
+    pub fn layer_mut(&mut self, z: usize) -> &mut Maze {
+        &mut self.layers[z]
+    }
+
+    pub fn is_valid_coord(&self, coord: &Coord3D) -> bool {
+        coord.0 < self.size.width && coord.1 < self.size.height && coord.2 < self.depth
+    }
+
+    /// Whether a stair leads from `(x, y, z)` up to `(x, y, z + 1)`.
+    pub fn is_connected_vertically(&self, coord: Coord3D) -> bool {
+        self.stairs.contains(&coord)
+    }
+
+    pub fn connect_vertically(&mut self, coord: Coord3D) {
+        assert!(
+            self.is_valid_coord(&(coord.0, coord.1, coord.2 + 1)),
+            "no layer above {:?}",
+            coord
+        );
+        self.stairs.insert(coord);
+    }
+
+    pub fn disconnect_vertically(&mut self, coord: Coord3D) {
+        self.stairs.remove(&coord);
+    }
+
+    /// Cells reachable in one move from `coord`, within its layer or by stairs.
+    pub fn get_open_neighbor_coords(&self, coord: Coord3D) -> Vec<Coord3D> {
+        let (x, y, z) = coord;
+        let mut neighbors: Vec<Coord3D> = self.layers[z]
+            .get_open_neighbor_coords((x, y))
             .into_iter()
-            .filter(|(coord, _)| self.left_to_visit.contains(coord))
+            .map(|(i, j)| (i, j, z))
+            .collect();
+        if self.is_connected_vertically(coord) {
+            neighbors.push((x, y, z + 1));
+        }
+        if z > 0 && self.is_connected_vertically((x, y, z - 1)) {
+            neighbors.push((x, y, z - 1));
+        }
+        neighbors
+    }
+
+    /// Carves a perfect maze on every layer, then one guaranteed stair between each
+    /// pair of adjacent layers plus a few random extra ones.
+    pub fn generate_seeded(&mut self, seed: u64) {
+        const EXTRA_STAIR_PROBABILITY: f64 = 0.02;
+
+        let mut rng = Pcg64::seed_from_u64(seed);
+        for layer in self.layers.iter_mut() {
+            let mut mazegen = MazeGen::new(&self.size);
+            mazegen.generate_seeded(rng.gen());
+            *layer = mazegen.maze;
+        }
+
+        self.stairs.clear();
+        for z in 1..self.depth {
+            let x = rng.gen_range(0..self.size.width);
+            let y = rng.gen_range(0..self.size.height);
+            self.stairs.insert((x, y, z - 1));
+            for i in 0..self.size.width {
+                for j in 0..self.size.height {
+                    if rng.gen_bool(EXTRA_STAIR_PROBABILITY) {
+                        self.stairs.insert((i, j, z - 1));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Hexagon-shaped maze of hexagonal cells in axial coordinates.
+pub struct HexMaze {
+    pub radius: usize,
+    walls: BTreeMap<HexCoord, BTreeSet<HexDirection>>,
+}
+
+impl HexMaze {
+    /// All walls enabled, cells within `radius` steps of the center.
+    pub fn new(radius: usize) -> Self {
+        let mut maze = Self {
+            radius,
+            walls: BTreeMap::new(),
+        };
+        for coord in maze.coords() {
+            let walls = ALL_HEX_DIRECTIONS.iter().map(|dir| **dir).collect();
+            maze.walls.insert(coord, walls);
+        }
+        maze
+    }
+
+    /// Every cell, row by row from the top.
+    pub fn coords(&self) -> Vec<HexCoord> {
+        let radius = self.radius as isize;
+        (-radius..=radius)
+            .flat_map(|r| {
+                let q_min = (-radius).max(-radius - r);
+                let q_max = radius.min(radius - r);
+                (q_min..=q_max).map(move |q| (q, r))
+            })
             .collect()
     }
 
-    // TODO: add seed
-    pub fn generate(&mut self) {
-        // reset maze
-        self.maze.enable_all_walls();
-        self.path_stack.clear();
+    pub fn is_valid_coord(&self, coord: &HexCoord) -> bool {
+        let radius = self.radius as isize;
+        let (q, r) = *coord;
+        q.abs() <= radius && r.abs() <= radius && (q + r).abs() <= radius
+    }
 
-        let mut rng = Pcg64::seed_from_u64(1512);
+    pub fn cell_count(&self) -> usize {
+        self.walls.len()
+    }
 
-        let mut coord: Coord = (0, 0);
-        self.left_to_visit.remove(&coord);
+    pub fn get_neighbor_coords_and_dirs(&self, coord: HexCoord) -> Vec<(HexCoord, HexDirection)> {
+        ALL_HEX_DIRECTIONS
+            .iter()
+            .map(|dir| {
+                let (dq, dr) = dir.offset();
+                ((coord.0 + dq, coord.1 + dr), **dir)
+            })
+            .filter(|(next, _)| self.is_valid_coord(next))
+            .collect()
+    }
 
-        while !self.path_stack.is_empty() || !self.left_to_visit.is_empty() {
-            // one algo step: choose a direction or backtrack
-            match self
-                .get_valid_neighbor_coords_and_dirs(coord)
+    pub fn get_open_neighbor_coords(&self, coord: HexCoord) -> Vec<HexCoord> {
+        self.get_neighbor_coords_and_dirs(coord)
+            .into_iter()
+            .filter(|(_, dir)| !self.is_wall_enabled(coord, dir))
+            .map(|(next, _)| next)
+            .collect()
+    }
+
+    /// Outer walls are always enabled.
+    pub fn is_wall_enabled(&self, coord: HexCoord, dir: &HexDirection) -> bool {
+        self.walls[&coord].contains(dir)
+    }
+
+    /// Enables the wall on both cells sharing it.
+    pub fn enable_wall(&mut self, coord: HexCoord, dir: &HexDirection) {
+        self.set_wall(coord, dir, true);
+    }
+
+    /// Disables the wall on both cells sharing it; outer walls are left untouched.
+    pub fn disable_wall(&mut self, coord: HexCoord, dir: &HexDirection) {
+        self.set_wall(coord, dir, false);
+    }
+
+    fn set_wall(&mut self, coord: HexCoord, dir: &HexDirection, enabled: bool) {
+        let (dq, dr) = dir.offset();
+        let next = (coord.0 + dq, coord.1 + dr);
+        if !self.is_valid_coord(&next) {
+            return;
+        }
+
+        for (coord, dir) in [(coord, *dir), (next, dir.opposite())].iter() {
+            let walls = self.walls.get_mut(coord).unwrap();
+            if enabled {
+                walls.insert(*dir);
+            } else {
+                walls.remove(dir);
+            }
+        }
+    }
+
+    /// Recursive backtracker from the center cell.
+    pub fn generate_seeded(&mut self, seed: u64) {
+        let mut rng = Pcg64::seed_from_u64(seed);
+        for walls in self.walls.values_mut() {
+            walls.extend(ALL_HEX_DIRECTIONS.iter().map(|dir| **dir));
+        }
+
+        let mut visited = BTreeSet::new();
+        let mut path_stack = vec![(0, 0)];
+        visited.insert((0, 0));
+
+        while let Some(&coord) = path_stack.last() {
+            let choice = self
+                .get_neighbor_coords_and_dirs(coord)
                 .into_iter()
-                .choose(&mut rng)
-            {
+                .filter(|(next, _)| !visited.contains(next))
+                .choose(&mut rng);
+            match choice {
                 None => {
-                    coord = self.path_stack.pop().unwrap();
+                    path_stack.pop();
                 }
-                Some((next_coord, dir)) => {
-                    // remove wall between current and next cell
-                    self.maze.disable_wall(coord, &dir);
+                Some((next, dir)) => {
+                    self.disable_wall(coord, &dir);
+                    visited.insert(next);
+                    path_stack.push(next);
+                }
+            }
+        }
+    }
+}
+
+/// Circular maze of concentric rings around a single center cell, outer rings being
+/// split into more cells so they keep roughly square proportions.
+pub struct ThetaMaze {
+    pub rings: usize,
+    ring_sizes: Vec<usize>,
+    // open passages, each pair stored with its smaller coord first
+    passages: BTreeSet<(ThetaCoord, ThetaCoord)>,
+}
+
+impl ThetaMaze {
+    /// All walls enabled. `rings` counts the center cell as the first ring.
+    pub fn new(rings: usize) -> Self {
+        let ring_height = 1.0 / rings as f64;
+        let mut ring_sizes = Vec::new();
+        for ring in 0..rings {
+            if ring == 0 {
+                ring_sizes.push(1);
+                continue;
+            }
+
+            // split each inner cell into as many cells as fit in its outer arc
+            let previous = ring_sizes[ring - 1];
+            let circumference = 2.0 * core::f64::consts::PI * ring as f64 * ring_height;
+            let ratio = (circumference / previous as f64 / ring_height + 0.5) as usize;
+            ring_sizes.push(previous * ratio.max(1));
+        }
+
+        Self {
+            rings,
+            ring_sizes,
+            passages: BTreeSet::new(),
+        }
+    }
+
+    pub fn cells_in_ring(&self, ring: usize) -> usize {
+        self.ring_sizes.get(ring).copied().unwrap_or(0)
+    }
+
+    /// Every cell, from the center ring outward.
+    pub fn coords(&self) -> Vec<ThetaCoord> {
+        self.ring_sizes
+            .iter()
+            .enumerate()
+            .flat_map(|(ring, &count)| (0..count).map(move |i| (ring, i)))
+            .collect()
+    }
+
+    pub fn is_valid_coord(&self, coord: &ThetaCoord) -> bool {
+        coord.1 < self.cells_in_ring(coord.0)
+    }
+
+    /// Clockwise and counterclockwise cells of the same ring, then the inward cell,
+    /// then the outward ones.
+    pub fn get_neighbor_coords(&self, coord: ThetaCoord) -> Vec<ThetaCoord> {
+        let (ring, i) = coord;
+        let count = self.cells_in_ring(ring);
+        let mut neighbors = Vec::new();
+
+        if count > 1 {
+            neighbors.push((ring, (i + 1) % count));
+            neighbors.push((ring, (i + count - 1) % count));
+        }
+        if ring > 0 {
+            let ratio = count / self.cells_in_ring(ring - 1);
+            neighbors.push((ring - 1, i / ratio));
+        }
+        if ring + 1 < self.rings {
+            let ratio = self.cells_in_ring(ring + 1) / count;
+            neighbors.extend((i * ratio..(i + 1) * ratio).map(|j| (ring + 1, j)));
+        }
+
+        neighbors.dedup();
+        neighbors
+    }
+
+    pub fn get_open_neighbor_coords(&self, coord: ThetaCoord) -> Vec<ThetaCoord> {
+        self.get_neighbor_coords(coord)
+            .into_iter()
+            .filter(|next| self.is_connected(coord, *next))
+            .collect()
+    }
+
+    /// Whether an open passage joins `a` and `b`.
+    pub fn is_connected(&self, a: ThetaCoord, b: ThetaCoord) -> bool {
+        self.passages.contains(&(a.min(b), a.max(b)))
+    }
+
+    /// Opens the wall between two neighboring cells. Panics if they aren't neighbors.
+    pub fn connect(&mut self, a: ThetaCoord, b: ThetaCoord) {
+        assert!(
+            self.is_valid_coord(&a) && self.get_neighbor_coords(a).contains(&b),
+            "cells {:?} and {:?} aren't neighbors",
+            a,
+            b
+        );
+        self.passages.insert((a.min(b), a.max(b)));
+    }
+
+    pub fn disconnect(&mut self, a: ThetaCoord, b: ThetaCoord) {
+        self.passages.remove(&(a.min(b), a.max(b)));
+    }
+
+    /// Recursive backtracker from the center cell.
+    pub fn generate_seeded(&mut self, seed: u64) {
+        let mut rng = Pcg64::seed_from_u64(seed);
+        self.passages.clear();
+        if self.rings == 0 {
+            return;
+        }
 
-                    self.path_stack.push(coord);
-                    coord = next_coord;
-                    self.left_to_visit.remove(&coord);
+        let mut visited = BTreeSet::new();
+        let mut path_stack = vec![(0, 0)];
+        visited.insert((0, 0));
+
+        while let Some(&coord) = path_stack.last() {
+            let choice = self
+                .get_neighbor_coords(coord)
+                .into_iter()
+                .filter(|next| !visited.contains(next))
+                .choose(&mut rng);
+            match choice {
+                None => {
+                    path_stack.pop();
+                }
+                Some(next) => {
+                    self.connect(coord, next);
+                    visited.insert(next);
+                    path_stack.push(next);
                 }
             }
         }
     }
 }
 
-pub fn gen_maze(size: &Size) -> Maze {
+/// Unbounded maze made of `chunk_size` tiles that are generated the first time they
+/// are needed and cached. Each chunk is a perfect maze with a single opening in each
+/// of its four borders; everything about a chunk and its borders derives from the
+/// world seed and the chunk position, so the world doesn't depend on visit order.
+#[derive(Clone)]
+pub struct WorldMaze {
+    chunk_size: Size,
+    seed: u64,
+    chunks: BTreeMap<(i64, i64), Maze>,
+}
+
+impl WorldMaze {
+    pub fn new(chunk_size: Size, seed: u64) -> Self {
+        Self {
+            chunk_size,
+            seed,
+            chunks: BTreeMap::new(),
+        }
+    }
+
+    /// The chunk at chunk position `chunk`, covering global cells from
+    /// `(chunk.0 * width, chunk.1 * height)`, generated if needed.
+    pub fn chunk(&mut self, chunk: (i64, i64)) -> &Maze {
+        if !self.chunks.contains_key(&chunk) {
+            let maze = self.generate_chunk(chunk);
+            self.chunks.insert(chunk, maze);
+        }
+
+        &self.chunks[&chunk]
+    }
+
+    /// True when the wall on the `dir` side of the cell at `global` is open, generating
+    /// the chunk owning that cell if needed.
+    pub fn cell_open(&mut self, global: (i64, i64), dir: &TileDirection) -> bool {
+        let (width, height) = (self.chunk_size.width as i64, self.chunk_size.height as i64);
+        let chunk = (global.0.div_euclid(width), global.1.div_euclid(height));
+        let local = (
+            global.0.rem_euclid(width) as usize,
+            global.1.rem_euclid(height) as usize,
+        );
+
+        !self.chunk(chunk).is_wall_enabled(local, dir)
+    }
+
+    // seed of the chunk's maze, then the openings in its east and south borders
+    fn chunk_draws(&self, chunk: (i64, i64)) -> (u64, usize, usize) {
+        let mut rng = Pcg64::new(
+            ((self.seed as u128) << 64) | chunk.0 as u64 as u128,
+            chunk.1 as u64 as u128,
+        );
+
+        (
+            rng.gen(),
+            rng.gen_range(0..self.chunk_size.height),
+            rng.gen_range(0..self.chunk_size.width),
+        )
+    }
 
+    fn generate_chunk(&self, chunk: (i64, i64)) -> Maze {
+        let (seed, east_opening, south_opening) = self.chunk_draws(chunk);
+        // the west and north borders belong to the neighbors
+        let (_, west_opening, _) = self.chunk_draws((chunk.0.wrapping_sub(1), chunk.1));
+        let (_, _, north_opening) = self.chunk_draws((chunk.0, chunk.1.wrapping_sub(1)));
+
+        let mut generator = MazeGen::from_maze(Maze::new_with_border(&self.chunk_size, false));
+        generator.generate_seeded(seed);
+        let mut maze = generator.maze;
+
+        let (width, height) = (self.chunk_size.width, self.chunk_size.height);
+        for j in 0..height {
+            if j != west_opening {
+                maze.enable_wall((0, j), &TileDirection::WEST);
+            }
+            if j != east_opening {
+                maze.enable_wall((width - 1, j), &TileDirection::EAST);
+            }
+        }
+        for i in 0..width {
+            if i != north_opening {
+                maze.enable_wall((i, 0), &TileDirection::NORTH);
+            }
+            if i != south_opening {
+                maze.enable_wall((i, height - 1), &TileDirection::SOUTH);
+            }
+        }
+
+        maze
+    }
+}
+
+#[cfg(feature = "std")]
+pub fn gen_maze(size: &Size) -> Maze {
     let mut mazegen = MazeGen::new(size);
     mazegen.generate();
 
     mazegen.maze
 }
+
+pub fn gen_maze_seeded(size: &Size, seed: u64) -> Maze {
+    let mut mazegen = MazeGen::new(size);
+    mazegen.generate_seeded(seed);
+
+    mazegen.maze
+}
+
+/// `gen_maze_seeded(size, layout_seed)` with difficulty varied by a second seed:
+/// `variation_seed` alone picks the entrance on the top border, the exit on the bottom
+/// border and how many dead ends get braided, up to half of them.
+pub fn gen_maze_split_seed(size: &Size, layout_seed: u64, variation_seed: u64) -> Maze {
+    const MAX_BRAID: f64 = 0.5;
+
+    let mut maze = gen_maze_seeded(size, layout_seed);
+
+    let mut rng = Pcg64::seed_from_u64(variation_seed);
+    maze.place_opposite_entrances(rng.gen());
+    maze.braid(rng.gen(), rng.gen_range(0.0..=MAX_BRAID));
+
+    maze
+}
+
+/// Splits the grid into `regions` vertical bands generated on their own threads, then
+/// joins each pair of neighboring bands through one random passage. The output only
+/// depends on the inputs, not on thread scheduling.
+#[cfg(feature = "std")]
+pub fn gen_maze_parallel(size: &Size, seed: u64, regions: usize) -> Maze {
+    let regions = regions.clamp(1, size.width.max(1));
+    let mut rng = Pcg64::seed_from_u64(seed);
+
+    // bands as wide as possible, the first ones one column wider
+    let mut bands = Vec::new();
+    let mut x = 0;
+    for region in 0..regions {
+        let width = size.width / regions + usize::from(region < size.width % regions);
+        let band_size = Size {
+            width,
+            height: size.height,
+        };
+        let band_seed: u64 = rng.gen();
+        let handle = std::thread::spawn(move || gen_maze_seeded(&band_size, band_seed));
+        bands.push((x, handle));
+        x += width;
+    }
+
+    let mut maze = Maze::new(size);
+    maze.enable_all_walls();
+    for (i, (offset, handle)) in bands.into_iter().enumerate() {
+        let band = handle.join().unwrap();
+        for ((x1, y1), (x2, y2)) in band.passages() {
+            let from = (x1 + offset, y1);
+            let dir = maze.get_dir_between(from, (x2 + offset, y2)).unwrap();
+            maze.disable_wall(from, &dir);
+        }
+        if i > 0 {
+            let y = rng.gen_range(0..size.height);
+            maze.disable_wall((offset, y), &TileDirection::WEST);
+        }
+    }
+
+    maze
+}
+
+#[cfg(feature = "std")]
+pub fn gen_maze_3d(width: usize, height: usize, depth: usize) -> Maze3D {
+    gen_maze_3d_seeded(width, height, depth, Pcg64::from_entropy().gen())
+}
+
+pub fn gen_maze_3d_seeded(width: usize, height: usize, depth: usize, seed: u64) -> Maze3D {
+    let mut maze = Maze3D::new(width, height, depth);
+    maze.generate_seeded(seed);
+
+    maze
+}
+
+#[cfg(feature = "std")]
+pub fn gen_hex_maze(radius: usize) -> HexMaze {
+    gen_hex_maze_seeded(radius, Pcg64::from_entropy().gen())
+}
+
+pub fn gen_hex_maze_seeded(radius: usize, seed: u64) -> HexMaze {
+    let mut maze = HexMaze::new(radius);
+    maze.generate_seeded(seed);
+
+    maze
+}
+
+#[cfg(feature = "std")]
+pub fn gen_theta_maze(rings: usize) -> ThetaMaze {
+    gen_theta_maze_seeded(rings, Pcg64::from_entropy().gen())
+}
+
+pub fn gen_theta_maze_seeded(rings: usize, seed: u64) -> ThetaMaze {
+    let mut maze = ThetaMaze::new(rings);
+    maze.generate_seeded(seed);
+
+    maze
+}
+
+#[cfg(test)]
+mod tests;
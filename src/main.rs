@@ -1,57 +1,107 @@
 use glutin_window::GlutinWindow as Window;
-use mazegen::{Maze, Size, TileDirection, ALL_TILE_DIRECTIONS};
+use mazegen::{MazeGen, RenderConfig, Size, TileDirection, ALL_TILE_DIRECTIONS};
 use opengl_graphics::{GlGraphics, OpenGL};
 use piston::event_loop::{EventSettings, Events};
-use piston::input::{RenderArgs, RenderEvent, UpdateArgs, UpdateEvent};
+use piston::input::{Button, Key, PressEvent, RenderArgs, RenderEvent, UpdateArgs, UpdateEvent};
 use piston::window::WindowSettings;
 
 pub struct App {
     gl: GlGraphics, // OpenGL drawing backend.
+    heat_map: bool, // shade cells by distance from the entrance
+    mazegen: MazeGen,
+    distances: Vec<Vec<Option<usize>>>, // from the entrance, for the heat map
+    config: RenderConfig,
 }
 
-const WALL_THICKNESS: f64 = 1.0;
-const CELL_SIZE: f64 = 25.0;
-const CELL_MARGIN: f64 = 2.0;
-const CELL_FULL_SIZE: f64 = (WALL_THICKNESS + CELL_MARGIN) * 2.0 + CELL_SIZE;
-
 impl App {
-    fn render(&mut self, args: &RenderArgs, maze: &Maze) {
+    // carves a new maze from a fresh seed, printed so it can be reproduced
+    fn regenerate(&mut self) {
+        let seed = self.mazegen.generate();
+        println!("seed: {}", seed);
+
+        let maze = &self.mazegen.maze;
+        self.distances = maze.distances(maze.entrance);
+    }
+
+    fn render(&mut self, args: &RenderArgs) {
         use graphics::*;
 
         const BLACK: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
         const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
-
-        let horizontal_wall = rectangle::rectangle_by_corners(0.0, 0.0, CELL_SIZE, WALL_THICKNESS);
-        let vertical_wall = rectangle::rectangle_by_corners(0.0, 0.0, WALL_THICKNESS, CELL_SIZE);
+        const GRAY: [f32; 4] = [0.5, 0.5, 0.5, 1.0];
+
+        let RenderConfig {
+            wall_thickness,
+            cell_size,
+            cell_margin,
+        } = self.config;
+        let config = self.config;
+
+        let horizontal_wall = rectangle::rectangle_by_corners(0.0, 0.0, cell_size, wall_thickness);
+        let vertical_wall = rectangle::rectangle_by_corners(0.0, 0.0, wall_thickness, cell_size);
+        let cell_background = rectangle::square(0.0, 0.0, cell_size);
+
+        let max_distance = self
+            .distances
+            .iter()
+            .flatten()
+            .flatten()
+            .max()
+            .copied()
+            .unwrap_or(0);
+        let heat_map = self.heat_map;
+        let maze = &self.mazegen.maze;
+        let distances = &self.distances;
 
         self.gl.draw(args.viewport(), |c, gl| {
             // Clear the screen.
             clear(BLACK, gl);
 
+            if heat_map {
+                for (i, column) in distances.iter().enumerate() {
+                    for (j, distance) in column.iter().enumerate() {
+                        // blue near the entrance, red far away
+                        let color = match distance {
+                            None => GRAY,
+                            Some(distance) => {
+                                let t = *distance as f32 / max_distance.max(1) as f32;
+                                [t, 0.0, 1.0 - t, 1.0]
+                            }
+                        };
+
+                        let (x, y) = config.cell_origin((i, j));
+                        let offset = cell_margin + wall_thickness;
+                        let transform = c.transform.trans(x, y).trans(offset, offset);
+
+                        rectangle(color, cell_background, transform, gl);
+                    }
+                }
+            }
+
             for i in 0..maze.size.width {
                 for j in 0..maze.size.height {
                     for direction in ALL_TILE_DIRECTIONS.iter() {
                         if maze.is_wall_enabled((i, j), direction) {
                             let (trans_x, trans_y, wall) = match direction {
                                 TileDirection::NORTH => {
-                                    (CELL_MARGIN + WALL_THICKNESS, CELL_MARGIN, &horizontal_wall)
+                                    (cell_margin + wall_thickness, cell_margin, &horizontal_wall)
                                 }
                                 TileDirection::WEST => {
-                                    (CELL_MARGIN, CELL_MARGIN + WALL_THICKNESS, &vertical_wall)
+                                    (cell_margin, cell_margin + wall_thickness, &vertical_wall)
                                 }
                                 TileDirection::SOUTH => (
-                                    CELL_MARGIN + WALL_THICKNESS,
-                                    CELL_MARGIN + WALL_THICKNESS * 2.0 + CELL_SIZE,
+                                    cell_margin + wall_thickness,
+                                    cell_margin + wall_thickness * 2.0 + cell_size,
                                     &horizontal_wall,
                                 ),
                                 TileDirection::EAST => (
-                                    CELL_MARGIN + WALL_THICKNESS + CELL_SIZE,
-                                    CELL_MARGIN + WALL_THICKNESS,
+                                    cell_margin + wall_thickness + cell_size,
+                                    cell_margin + wall_thickness,
                                     &vertical_wall,
                                 ),
                             };
 
-                            let (x, y) = (CELL_FULL_SIZE * (i as f64), CELL_FULL_SIZE * (j as f64));
+                            let (x, y) = config.cell_origin((i, j));
                             let transform = c.transform.trans(x, y).trans(trans_x, trans_y);
 
                             rectangle(RED, *wall, transform, gl);
@@ -68,19 +118,17 @@ impl App {
 }
 
 fn main() {
-    let maze = mazegen::gen_maze(&Size {
+    let size = Size {
         width: 20,
         height: 20,
-    });
+    };
 
     // Change this to OpenGL::V2_1 if not working.
     let opengl = OpenGL::V3_2;
 
     // Create an Glutin window.
-    let window_size = [
-        maze.size.width as f64 * CELL_FULL_SIZE,
-        maze.size.height as f64 * CELL_FULL_SIZE,
-    ];
+    let config = RenderConfig::default();
+    let window_size = config.window_size(&size);
 
     let mut window: Window = WindowSettings::new("spinning-square", window_size)
         .graphics_api(opengl)
@@ -91,12 +139,25 @@ fn main() {
     // Create a new game and run it.
     let mut app = App {
         gl: GlGraphics::new(opengl),
+        heat_map: false,
+        mazegen: MazeGen::new(&size),
+        distances: Vec::new(),
+        config,
     };
+    app.regenerate();
 
     let mut events = Events::new(EventSettings::new());
     while let Some(e) = events.next(&mut window) {
         if let Some(args) = e.render_args() {
-            app.render(&args, &maze);
+            app.render(&args);
+        }
+
+        match e.press_args() {
+            // H toggles the distance heat map
+            Some(Button::Keyboard(Key::H)) => app.heat_map = !app.heat_map,
+            // R carves a new maze
+            Some(Button::Keyboard(Key::R)) => app.regenerate(),
+            _ => {}
         }
 
         if let Some(args) = e.update_args() {
@@ -0,0 +1,2306 @@
+use super::*;
+
+fn size(width: usize, height: usize) -> Size {
+    Size { width, height }
+}
+
+#[test]
+fn is_solvable_rejects_sealed_endpoints() {
+    let mut maze = gen_maze_seeded(&size(5, 5), 1);
+    assert!(maze.is_solvable((0, 0), (2, 2)));
+
+    for dir in ALL_TILE_DIRECTIONS.iter() {
+        maze.enable_wall((2, 2), dir);
+    }
+    assert!(!maze.is_solvable((0, 0), (2, 2)));
+    assert!(!maze.is_solvable((2, 2), (0, 0)));
+    assert!(!maze.is_solvable((2, 2), (2, 2)));
+}
+
+#[test]
+fn is_solvable_rejects_out_of_bounds_endpoints() {
+    let maze = gen_maze_seeded(&size(4, 4), 1);
+
+    assert!(!maze.is_solvable((0, 0), (4, 0)));
+    assert!(!maze.is_solvable((0, 4), (0, 0)));
+    assert!(!maze.is_solvable((10, 10), (10, 10)));
+}
+
+// (x1, y1, x2, y2) of every <line> element
+fn svg_lines(svg: &str) -> Vec<(f64, f64, f64, f64)> {
+    let attribute = |line: &str, name: &str| -> f64 {
+        let start = line.find(&format!(" {}=\"", name)).unwrap() + name.len() + 3;
+        let end = start + line[start..].find('"').unwrap();
+        line[start..end].parse().unwrap()
+    };
+
+    svg.lines()
+        .filter(|line| line.starts_with("<line"))
+        .map(|line| {
+            (
+                attribute(line, "x1"),
+                attribute(line, "y1"),
+                attribute(line, "x2"),
+                attribute(line, "y2"),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn to_svg_fixed_centers_non_square_mazes() {
+    let maze = gen_maze_seeded(&size(4, 2), 3);
+    let svg = maze.to_svg_fixed(size(200, 200), 10.0);
+    assert!(svg.contains("width=\"200\" height=\"200\""));
+
+    let lines = svg_lines(&svg);
+    let min_x = lines.iter().map(|l| l.0.min(l.2)).fold(f64::MAX, f64::min);
+    let max_x = lines.iter().map(|l| l.0.max(l.2)).fold(f64::MIN, f64::max);
+    let min_y = lines.iter().map(|l| l.1.min(l.3)).fold(f64::MAX, f64::min);
+    let max_y = lines.iter().map(|l| l.1.max(l.3)).fold(f64::MIN, f64::max);
+
+    // limited by the width: 180 / 4 = 45 per cell on both axes
+    assert_eq!((min_x, max_x), (10.0, 190.0));
+    assert_eq!((min_y, max_y), (55.0, 145.0));
+    assert_eq!(max_x - min_x, 2.0 * (max_y - min_y));
+}
+
+#[test]
+fn generate_pack_matches_its_inputs() {
+    let sizes = [size(3, 3), size(5, 4), size(8, 8)];
+    let braids = [0.0, 0.5, 1.0];
+
+    let pack = MazeGen::generate_pack(7, &sizes, &braids).unwrap();
+    assert_eq!(pack.len(), sizes.len());
+    for (maze, size) in pack.iter().zip(sizes.iter()) {
+        assert_eq!(maze.size, *size);
+        assert_eq!(maze.reachable_count((0, 0)), size.width * size.height);
+    }
+    assert!(pack[0].is_perfect());
+
+    // reproducible, and each level only depends on its own index
+    assert!(MazeGen::generate_pack(7, &sizes, &braids).unwrap() == pack);
+    let shorter = MazeGen::generate_pack(7, &sizes[..2], &braids[..2]).unwrap();
+    assert!(shorter[..] == pack[..2]);
+}
+
+#[test]
+fn generate_pack_rejects_mismatched_lengths() {
+    let result = MazeGen::generate_pack(7, &[size(3, 3), size(4, 4)], &[0.5]);
+    assert_eq!(result.err(), Some(MazeError::LengthMismatch(2, 1)));
+}
+
+// consecutive cells of `path` are joined by open walls
+fn is_open_path(maze: &Maze, path: &[Coord]) -> bool {
+    path.windows(2)
+        .all(|step| maze.get_open_neighbor_coords(step[0]).contains(&step[1]))
+}
+
+#[test]
+fn solve_reduced_matches_bfs_length() {
+    let mut maze = gen_maze_seeded(&size(12, 9), 4);
+    maze.braid(4, 0.5);
+
+    let coords: Vec<Coord> = maze.iter_cells().map(|cell| cell.coord).collect();
+    for start in coords.iter().step_by(7) {
+        for end in coords.iter().step_by(5) {
+            let bfs = maze.solve_bfs(*start, *end).unwrap();
+            let reduced = maze.solve_reduced(*start, *end).unwrap();
+            assert_eq!(reduced.len(), bfs.len());
+            assert_eq!(reduced.first(), Some(start));
+            assert_eq!(reduced.last(), Some(end));
+            assert!(is_open_path(&maze, &reduced));
+        }
+    }
+}
+
+#[test]
+fn solve_reduced_handles_endpoints_inside_one_corridor() {
+    let mut maze = Maze::new(&size(5, 1));
+    maze.enable_all_walls();
+    for x in 0..4 {
+        maze.connect((x, 0), (x + 1, 0)).unwrap();
+    }
+
+    assert_eq!(maze.passage_degree((1, 0)), 2);
+    assert_eq!(
+        maze.solve_reduced((1, 0), (3, 0)),
+        Some(vec![(1, 0), (2, 0), (3, 0)])
+    );
+    assert_eq!(
+        maze.solve_reduced((3, 0), (1, 0)),
+        Some(vec![(3, 0), (2, 0), (1, 0)])
+    );
+    assert_eq!(maze.solve_reduced((2, 0), (2, 0)), Some(vec![(2, 0)]));
+}
+
+#[test]
+fn solve_reduced_returns_none_when_unreachable() {
+    let mut maze = Maze::new(&size(3, 3));
+    maze.enable_all_walls();
+    assert_eq!(maze.solve_reduced((0, 0), (2, 2)), None);
+    assert_eq!(maze.solve_reduced((0, 0), (3, 0)), None);
+}
+
+#[test]
+fn fingerprint_survives_clone_and_round_trip() {
+    let maze = gen_maze_seeded(&size(7, 5), 9);
+    let fingerprint = maze.fingerprint();
+
+    assert_eq!(maze.clone().fingerprint(), fingerprint);
+    let rebuilt = Maze::from_wall_grid(&maze.to_wall_grid()).unwrap();
+    assert_eq!(rebuilt.fingerprint(), fingerprint);
+
+    let mut mutated = maze.clone();
+    let (a, b) = mutated.passages().next().unwrap();
+    mutated.disconnect(a, b).unwrap();
+    assert_ne!(mutated.fingerprint(), fingerprint);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn fingerprint_survives_ascii_round_trip() {
+    let maze = gen_maze_seeded(&size(6, 4), 2);
+    let parsed = Maze::from_ascii(&maze.to_string()).unwrap();
+    assert_eq!(parsed.fingerprint(), maze.fingerprint());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn generate_budgeted_runs_out_on_a_large_maze() {
+    let maze_size = size(300, 300);
+    let mut generator = MazeGen::new(&maze_size);
+
+    assert!(!generator.generate_budgeted(1, Duration::from_nanos(1)));
+    let carved = generator.maze.reachable_count((0, 0));
+    assert!(carved > 1 && carved < maze_size.width * maze_size.height);
+    // the carved part is still a tree
+    assert_eq!(generator.maze.passages().count(), carved - 1);
+    assert_eq!(generator.last_seed(), Some(1));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn generate_budgeted_resumes_after_running_out() {
+    let mut generator = MazeGen::new(&size(40, 40));
+    assert!(!generator.generate_budgeted(8, Duration::from_nanos(1)));
+    while generator.step() {}
+
+    let mut reference = MazeGen::new(&size(40, 40));
+    reference.generate_seeded(8);
+    assert!(generator.maze == reference.maze);
+
+    // a finished budgeted run is the seeded maze too
+    assert!(generator.generate_budgeted(8, Duration::from_secs(60)));
+    assert!(generator.maze == reference.maze);
+}
+
+fn assert_opposite_entrances(maze: &Maze) {
+    assert_eq!(maze.entrance.1, 0);
+    assert_eq!(maze.exit.1, maze.size.height - 1);
+    assert!(!maze.is_wall_enabled(maze.entrance, &TileDirection::NORTH));
+    assert!(!maze.is_wall_enabled(maze.exit, &TileDirection::SOUTH));
+
+    let path = maze.solve_bfs(maze.entrance, maze.exit).unwrap();
+    assert!(is_open_path(maze, &path));
+}
+
+#[test]
+fn place_opposite_entrances_opens_top_and_bottom() {
+    let mut maze = gen_maze_seeded(&size(9, 6), 5);
+    maze.place_opposite_entrances(11);
+
+    assert_opposite_entrances(&maze);
+    // the old openings are closed again and nothing else was carved
+    assert!(maze.is_perfect());
+    let top_openings = (0..9)
+        .filter(|x| !maze.is_wall_enabled((*x, 0), &TileDirection::NORTH))
+        .count();
+    assert_eq!(top_openings, 1);
+}
+
+#[test]
+fn place_opposite_entrances_carves_a_connection() {
+    for seed in 0..10 {
+        let mut maze = Maze::new(&size(6, 4));
+        maze.enable_all_walls();
+        maze.place_opposite_entrances(seed);
+
+        assert_opposite_entrances(&maze);
+    }
+}
+
+#[test]
+fn origin_shift_stays_perfect() {
+    let mut generator = MazeGen::new(&size(8, 6));
+    generator.origin_shift_init(1);
+    for step in 0..500 {
+        generator.origin_shift_step(step);
+        assert!(generator.maze.is_perfect());
+    }
+}
+
+#[test]
+fn origin_shift_restarts_after_another_generator() {
+    let mut generator = MazeGen::new(&size(8, 6));
+    generator.origin_shift_init(1);
+    generator.generate_prim(3);
+    for _ in 0..50 {
+        generator.origin_shift_step(1);
+    }
+    assert!(generator.maze.is_perfect());
+
+    generator.origin_shift_init(1);
+    generator.generate_recursive_division(3);
+    for _ in 0..50 {
+        generator.origin_shift_step(1);
+    }
+    assert!(generator.maze.is_perfect());
+}
+
+#[test]
+fn origin_shift_steps_follow_the_init_seed() {
+    let mut a = MazeGen::new(&size(8, 6));
+    let mut b = MazeGen::new(&size(8, 6));
+    a.origin_shift_init(1);
+    b.origin_shift_init(1);
+
+    // once initialized the step seed is ignored
+    let mut fingerprints = BTreeSet::new();
+    for step in 0..20 {
+        a.origin_shift_step(7);
+        b.origin_shift_step(step);
+        assert!(a.maze == b.maze);
+        fingerprints.insert(a.maze.fingerprint());
+    }
+    // and a constant step seed doesn't replay the same move
+    assert!(fingerprints.len() > 2);
+}
+
+#[test]
+fn choke_points_rank_a_shared_corridor_first() {
+    // two open rooms joined by the single corridor (1,1) - (2,1) - (3,1)
+    let mut maze = Maze::new(&size(5, 3));
+    maze.enable_all_walls();
+    for (a, b) in [((0, 0), (1, 0)), ((0, 1), (1, 1)), ((0, 2), (1, 2))].iter() {
+        maze.connect(*a, *b).unwrap();
+    }
+    for (a, b) in [
+        ((0, 0), (0, 1)),
+        ((0, 1), (0, 2)),
+        ((1, 0), (1, 1)),
+        ((1, 1), (1, 2)),
+    ]
+    .iter()
+    {
+        maze.connect(*a, *b).unwrap();
+    }
+    for (a, b) in [
+        ((3, 0), (4, 0)),
+        ((3, 2), (4, 2)),
+        ((4, 0), (4, 1)),
+        ((4, 1), (4, 2)),
+    ]
+    .iter()
+    {
+        maze.connect(*a, *b).unwrap();
+    }
+    maze.connect((1, 1), (2, 1)).unwrap();
+    maze.connect((2, 1), (3, 1)).unwrap();
+    maze.connect((3, 1), (4, 1)).unwrap();
+
+    let sources = [(0, 0), (0, 2), (1, 0), (1, 2)];
+    let ranked = maze.choke_points(&sources, (4, 1));
+
+    let shared: BTreeSet<Coord> = ranked[..3].iter().map(|(coord, _)| *coord).collect();
+    let corridor: BTreeSet<Coord> = [(1, 1), (2, 1), (3, 1)].iter().copied().collect();
+    assert_eq!(shared, corridor);
+    assert!(ranked[..3].iter().all(|(_, count)| *count == sources.len()));
+    assert!(ranked[3..].iter().all(|(_, count)| *count < sources.len()));
+}
+
+// interior walls (east and south) of `a` and `b` agree on the `width`x`height` top-left region
+fn same_interior_walls<T, U>(a: &Maze<T>, b: &Maze<U>, width: usize, height: usize) -> bool {
+    (0..width).all(|i| {
+        (0..height).all(|j| {
+            (i + 1 == width
+                || a.is_wall_enabled((i, j), &TileDirection::EAST)
+                    == b.is_wall_enabled((i, j), &TileDirection::EAST))
+                && (j + 1 == height
+                    || a.is_wall_enabled((i, j), &TileDirection::SOUTH)
+                        == b.is_wall_enabled((i, j), &TileDirection::SOUTH))
+        })
+    })
+}
+
+#[test]
+fn resized_grows_with_walled_cells() {
+    let mut generator = MazeGen::from_maze(Maze::new_with_data(
+        &size(4, 3),
+        Topology::Planar,
+        |(i, j)| i * 10 + j,
+    ));
+    generator.generate_seeded(6);
+    let maze = generator.maze;
+
+    let grown = maze.resized(size(6, 5));
+    assert_eq!((grown.size.width, grown.size.height), (6, 5));
+    assert!(same_interior_walls(&maze, &grown, 4, 3));
+    assert_eq!(grown.cell_data((3, 2)), Some(&32));
+    assert_eq!(grown.cell_data((5, 4)), Some(&0));
+
+    // every cell outside the old region is closed off
+    for cell in grown
+        .iter_cells()
+        .filter(|cell| cell.coord.0 >= 4 || cell.coord.1 >= 3)
+    {
+        assert_eq!(grown.passage_degree(cell.coord), 0);
+    }
+    assert!(grown.is_wall_enabled((5, 0), &TileDirection::EAST));
+    assert!(grown.is_wall_enabled((2, 4), &TileDirection::SOUTH));
+}
+
+#[test]
+fn resized_shrinks_by_cropping() {
+    let maze = gen_maze_seeded(&size(6, 5), 6);
+
+    let shrunk = maze.resized(size(3, 2));
+    assert_eq!((shrunk.size.width, shrunk.size.height), (3, 2));
+    assert_eq!(shrunk.iter_cells().count(), 6);
+    assert!(same_interior_walls(&maze, &shrunk, 3, 2));
+    for j in 0..2 {
+        assert!(shrunk.is_wall_enabled((2, j), &TileDirection::EAST));
+    }
+    assert!(shrunk.is_wall_enabled((0, 1), &TileDirection::SOUTH));
+}
+
+#[test]
+fn tree_edges_span_the_reachable_component() {
+    let maze = gen_maze_seeded(&size(7, 6), 8);
+    let edges = maze.tree_edges((3, 3));
+    assert_eq!(edges.len(), maze.reachable_count((3, 3)) - 1);
+
+    // braided mazes still get a tree: every cell is a child at most once
+    let mut braided = maze.clone();
+    braided.braid(8, 1.0);
+    let edges = braided.tree_edges((3, 3));
+    assert_eq!(edges.len(), braided.reachable_count((3, 3)) - 1);
+    let children: BTreeSet<Coord> = edges.iter().map(|(_, child)| *child).collect();
+    assert_eq!(children.len(), edges.len());
+    assert!(!children.contains(&(3, 3)));
+
+    // only the component of the root is covered
+    let mut split = Maze::new(&size(4, 1));
+    split.enable_all_walls();
+    split.connect((0, 0), (1, 0)).unwrap();
+    split.connect((2, 0), (3, 0)).unwrap();
+    assert_eq!(split.tree_edges((0, 0)), vec![((0, 0), (1, 0))]);
+}
+
+// top-left corners of 2x2 blocks whose four cells are all joined to each other
+fn open_blocks(maze: &Maze) -> Vec<Coord> {
+    let mut blocks = Vec::new();
+    for i in 0..maze.size.width - 1 {
+        for j in 0..maze.size.height - 1 {
+            if !maze.is_wall_enabled((i, j), &TileDirection::EAST)
+                && !maze.is_wall_enabled((i, j), &TileDirection::SOUTH)
+                && !maze.is_wall_enabled((i + 1, j + 1), &TileDirection::NORTH)
+                && !maze.is_wall_enabled((i + 1, j + 1), &TileDirection::WEST)
+            {
+                blocks.push((i, j));
+            }
+        }
+    }
+    blocks
+}
+
+#[test]
+fn cave_smooth_grows_caverns() {
+    let mut maze = gen_maze_seeded(&size(20, 12), 1);
+    assert!(open_blocks(&maze).is_empty());
+
+    maze.cave_smooth(2, 5);
+    assert!(!open_blocks(&maze).is_empty());
+    let mut again = gen_maze_seeded(&size(20, 12), 1);
+    again.cave_smooth(2, 5);
+    assert!(again == maze);
+
+    // filled cells are walled in until the maze is completed again
+    let mut generator = MazeGen::new(&maze.size);
+    generator.complete(&maze, 5);
+    assert_eq!(generator.maze.reachable_count((0, 0)), 20 * 12);
+    assert!(open_blocks(&generator.maze).len() >= open_blocks(&maze).len());
+}
+
+#[test]
+fn smooth_path_collapses_straight_corridors() {
+    let mut maze = Maze::new(&size(5, 3));
+    maze.enable_all_walls();
+    for x in 0..4 {
+        maze.connect((x, 1), (x + 1, 1)).unwrap();
+    }
+    let path = maze.solve_bfs((0, 1), (4, 1)).unwrap();
+    assert_eq!(path.len(), 5);
+    assert_eq!(maze.smooth_path(&path), vec![(0, 1), (4, 1)]);
+
+    // an L-shaped corridor keeps its corner
+    maze.connect((4, 1), (4, 2)).unwrap();
+    let path = maze.solve_bfs((0, 1), (4, 2)).unwrap();
+    assert_eq!(maze.smooth_path(&path), vec![(0, 1), (4, 1), (4, 2)]);
+
+    assert_eq!(maze.smooth_path(&[]), vec![]);
+    assert_eq!(maze.smooth_path(&[(2, 1)]), vec![(2, 1)]);
+}
+
+#[test]
+fn smooth_path_keeps_a_walkable_route() {
+    let maze = gen_maze_seeded(&size(10, 10), 3);
+    let path = maze.solve_bfs((0, 0), (9, 9)).unwrap();
+    let waypoints = maze.smooth_path(&path);
+
+    assert!(waypoints.len() <= path.len());
+    assert_eq!(waypoints.first(), Some(&(0, 0)));
+    assert_eq!(waypoints.last(), Some(&(9, 9)));
+    assert!(waypoints
+        .windows(2)
+        .all(|pair| maze.has_line_of_sight(pair[0], pair[1])));
+}
+
+#[test]
+fn generate_zoned_braids_inside_zones_only() {
+    let zone = Rect {
+        x: 0,
+        y: 0,
+        width: 6,
+        height: 5,
+    };
+    let mut generator = MazeGen::new(&size(12, 10));
+    generator.generate_zoned(4, vec![(zone, 1.0)]);
+
+    let dead_ends = generator.maze.dead_ends();
+    assert!(dead_ends.iter().all(|coord| !zone.contains(*coord)));
+    assert!(!dead_ends.is_empty());
+    assert_eq!(generator.maze.reachable_count((0, 0)), 12 * 10);
+
+    // overlapping zones: the first one wins
+    let mut generator = MazeGen::new(&size(12, 10));
+    generator.generate_zoned(
+        4,
+        vec![
+            (zone, 0.0),
+            (
+                Rect {
+                    x: 0,
+                    y: 0,
+                    width: 12,
+                    height: 10,
+                },
+                1.0,
+            ),
+        ],
+    );
+    let dead_ends = generator.maze.dead_ends();
+    assert!(dead_ends.iter().all(|coord| zone.contains(*coord)));
+}
+
+#[test]
+fn min_cut_counts_parallel_routes() {
+    let maze = gen_maze_seeded(&size(8, 8), 2);
+    assert_eq!(maze.min_cut((0, 0), (7, 7)), 1);
+    assert_eq!(maze.min_cut((3, 5), (6, 1)), 1);
+    assert_eq!(maze.min_cut((4, 4), (4, 4)), 0);
+
+    // a single loop around a 3x2 block gives two routes
+    let mut ring = Maze::new(&size(3, 2));
+    ring.enable_all_walls();
+    for (a, b) in [
+        ((0, 0), (1, 0)),
+        ((1, 0), (2, 0)),
+        ((2, 0), (2, 1)),
+        ((2, 1), (1, 1)),
+        ((1, 1), (0, 1)),
+        ((0, 1), (0, 0)),
+    ]
+    .iter()
+    {
+        ring.connect(*a, *b).unwrap();
+    }
+    assert_eq!(ring.min_cut((0, 0), (2, 0)), 2);
+    assert_eq!(ring.min_cut((0, 1), (2, 0)), 2);
+}
+
+#[test]
+fn to_svg_labels_every_cell_when_asked() {
+    let maze = gen_maze_seeded(&size(6, 4), 1);
+    assert!(!maze.to_svg(20.0, 2.0).contains("<text"));
+    assert_eq!(
+        maze.to_svg(20.0, 2.0),
+        maze.to_svg_with(20.0, 2.0, &SvgOptions::default())
+    );
+
+    let options = SvgOptions { label_cells: true };
+    let svg = maze.to_svg_with(20.0, 2.0, &options);
+    assert_eq!(svg.matches("<text").count(), 6 * 4);
+    // centered in cell (2, 1), shifted by half the wall thickness
+    assert!(svg.contains("<text x=\"51\" y=\"31\""));
+    assert!(svg.contains(">2,1</text>"));
+}
+
+#[test]
+fn world_maze_chunks_agree_on_shared_borders() {
+    let mut world = WorldMaze::new(size(5, 4), 12);
+
+    for chunk_y in -2..2 {
+        for chunk_x in -2..2 {
+            let (left, top) = (chunk_x * 5, chunk_y * 4);
+
+            // east border of this chunk against the west border of the next one
+            let mut openings = 0;
+            for y in top..top + 4 {
+                let open = world.cell_open((left + 4, y), &TileDirection::EAST);
+                assert_eq!(open, world.cell_open((left + 5, y), &TileDirection::WEST));
+                openings += open as usize;
+            }
+            assert_eq!(openings, 1);
+
+            let mut openings = 0;
+            for x in left..left + 5 {
+                let open = world.cell_open((x, top + 3), &TileDirection::SOUTH);
+                assert_eq!(open, world.cell_open((x, top + 4), &TileDirection::NORTH));
+                openings += open as usize;
+            }
+            assert_eq!(openings, 1);
+        }
+    }
+}
+
+#[test]
+fn world_maze_ignores_visit_order() {
+    let mut forward = WorldMaze::new(size(6, 6), 3);
+    let mut backward = WorldMaze::new(size(6, 6), 3);
+    let chunks = [(0, 0), (1, 0), (-1, 2), (7, -3)];
+
+    for chunk in chunks.iter() {
+        forward.chunk(*chunk);
+    }
+    for chunk in chunks.iter().rev() {
+        backward.chunk(*chunk);
+    }
+    for chunk in chunks.iter() {
+        let maze = forward.chunk(*chunk).clone();
+        assert!(&maze == backward.chunk(*chunk));
+        // perfect inside, the openings lead out of the chunk
+        assert_eq!(maze.passages().count(), 6 * 6 - 1);
+        assert_eq!(maze.reachable_count((0, 0)), 6 * 6);
+    }
+    let origin = forward.chunk((0, 0)).clone();
+    assert!(&origin != forward.chunk((1, 0)));
+}
+
+// number of groups of cells joined by open passages
+fn component_count(maze: &Maze) -> usize {
+    let mut seen = BTreeSet::new();
+    let mut count = 0;
+    for cell in maze.iter_cells() {
+        if seen.contains(&cell.coord) {
+            continue;
+        }
+        count += 1;
+        for (i, column) in maze.distances(cell.coord).iter().enumerate() {
+            for (j, distance) in column.iter().enumerate() {
+                if distance.is_some() {
+                    seen.insert((i, j));
+                }
+            }
+        }
+    }
+    count
+}
+
+#[test]
+fn generate_regions_makes_disconnected_trees() {
+    let mut generator = MazeGen::new(&size(10, 8));
+    generator.generate_regions(5, 3);
+
+    assert_eq!(component_count(&generator.maze), 3);
+    // a forest of 3 trees
+    assert_eq!(generator.maze.passages().count(), 10 * 8 - 3);
+
+    generator.generate_regions(5, 1);
+    assert!(generator.maze.is_perfect());
+}
+
+#[test]
+fn has_line_of_sight_needs_an_open_straight_corridor() {
+    let mut maze = Maze::new(&size(5, 3));
+    maze.enable_all_walls();
+    for x in 0..4 {
+        maze.connect((x, 1), (x + 1, 1)).unwrap();
+    }
+    maze.connect((2, 0), (2, 1)).unwrap();
+
+    assert!(maze.has_line_of_sight((0, 1), (4, 1)));
+    assert!(maze.has_line_of_sight((4, 1), (1, 1)));
+    assert!(maze.has_line_of_sight((2, 0), (2, 1)));
+
+    // walls in between, cells off the row or column, same cell
+    assert!(!maze.has_line_of_sight((0, 0), (4, 0)));
+    assert!(!maze.has_line_of_sight((2, 0), (2, 2)));
+    assert!(!maze.has_line_of_sight((2, 0), (4, 1)));
+    assert!(!maze.has_line_of_sight((1, 1), (1, 1)));
+    assert!(!maze.has_line_of_sight((0, 1), (5, 1)));
+
+    maze.disconnect((2, 1), (3, 1)).unwrap();
+    assert!(!maze.has_line_of_sight((0, 1), (4, 1)));
+    assert!(maze.has_line_of_sight((0, 1), (2, 1)));
+}
+
+#[test]
+fn flow_field_leads_every_cell_to_the_goal() {
+    let mut maze = gen_maze_seeded(&size(9, 7), 6);
+    maze.braid(6, 0.5);
+    let goal = (4, 3);
+    let field = maze.flow_field(goal);
+    let distances = maze.distances(goal);
+
+    assert_eq!(field[goal.0][goal.1], None);
+    for cell in maze.iter_cells() {
+        let mut coord = cell.coord;
+        let mut steps = 0;
+        while coord != goal {
+            let dir = field[coord.0][coord.1].unwrap();
+            assert!(!maze.is_wall_enabled(coord, &dir));
+            coord = maze
+                .get_neighbor_coords_and_dirs(coord)
+                .into_iter()
+                .find(|(_, neighbor_dir)| *neighbor_dir == dir)
+                .unwrap()
+                .0;
+            steps += 1;
+        }
+        // along a shortest route
+        assert_eq!(Some(steps), distances[cell.coord.0][cell.coord.1]);
+    }
+}
+
+#[test]
+fn to_ascii_aligned_lines_have_equal_widths() {
+    let maze = gen_maze_seeded(&size(7, 4), 2);
+    let ascii = maze.to_ascii_aligned();
+    let lines: Vec<&str> = ascii.lines().collect();
+
+    assert_eq!(lines.len(), 2 * 4 + 1);
+    assert!(lines
+        .iter()
+        .all(|line| line.chars().count() == 2 * (2 * 7 + 1)));
+    // sealed border apart from the entrance and exit
+    assert_eq!(lines[0], format!("##  {}", "##".repeat(13)));
+    assert!(lines
+        .iter()
+        .all(|line| line.starts_with("##") && line.ends_with("##")));
+}
+
+// corridor cells whose two passages aren't opposite each other
+fn turn_count(maze: &Maze) -> usize {
+    maze.iter_cells()
+        .filter(|cell| {
+            let open: Vec<TileDirection> = ALL_TILE_DIRECTIONS
+                .iter()
+                .filter(|dir| !maze.is_wall_enabled(cell.coord, dir))
+                .map(|dir| **dir)
+                .collect();
+            match open[..] {
+                [a, b] => !matches!(
+                    (a, b),
+                    (TileDirection::NORTH, TileDirection::SOUTH)
+                        | (TileDirection::EAST, TileDirection::WEST)
+                ),
+                _ => false,
+            }
+        })
+        .count()
+}
+
+#[test]
+fn generate_river_straightens_corridors() {
+    let mut generator = MazeGen::new(&size(20, 20));
+    generator.generate_river(3, 0.0);
+    let winding = turn_count(&generator.maze);
+    assert!(generator.maze.is_perfect());
+
+    generator.generate_river(3, 1.0);
+    let straight = turn_count(&generator.maze);
+    assert!(generator.maze.is_perfect());
+
+    assert!(winding > straight * 2);
+}
+
+#[test]
+fn to_daedalus_writes_a_monochrome_bitmap() {
+    let maze = gen_maze_seeded(&size(20, 3), 4);
+    let bytes = maze.to_daedalus();
+    let u32_at = |offset: usize| {
+        u32::from_le_bytes([
+            bytes[offset],
+            bytes[offset + 1],
+            bytes[offset + 2],
+            bytes[offset + 3],
+        ])
+    };
+    let u16_at = |offset: usize| u16::from_le_bytes([bytes[offset], bytes[offset + 1]]);
+
+    // a 41x7 grid, rows of 41 bits padded to 8 bytes
+    assert_eq!(&bytes[0..2], b"BM");
+    assert_eq!(u32_at(2) as usize, bytes.len());
+    assert_eq!(bytes.len(), 62 + 8 * 7);
+    assert_eq!(u32_at(6), 0);
+    assert_eq!(u32_at(10), 62);
+    assert_eq!(u32_at(14), 40);
+    assert_eq!((u32_at(18), u32_at(22)), (41, 7));
+    assert_eq!((u16_at(26), u16_at(28)), (1, 1));
+    assert_eq!((u32_at(30), u32_at(34)), (0, 8 * 7));
+    assert_eq!((u32_at(46), u32_at(50)), (2, 2));
+    assert_eq!(&bytes[54..62], &[0xff, 0xff, 0xff, 0, 0, 0, 0, 0]);
+
+    // the last row stored is the top one: wall, entrance above (0, 0), then walls
+    let top = &bytes[62 + 8 * 6..];
+    assert_eq!(top[0], 0b1011_1111);
+    assert_eq!(&top[1..5], &[0xff; 4]);
+    assert_eq!(top[5], 0b1000_0000);
+    assert_eq!(&top[6..8], &[0, 0]);
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn to_daedalus_decodes_as_the_wall_grid() {
+    let maze = gen_maze_seeded(&size(20, 3), 4);
+    let bitmap = image::load_from_memory_with_format(&maze.to_daedalus(), image::ImageFormat::Bmp)
+        .unwrap()
+        .to_luma8();
+    let grid = maze.to_wall_grid();
+
+    assert_eq!(bitmap.dimensions(), (41, 7));
+    for (x, column) in grid.iter().enumerate() {
+        for (y, is_wall) in column.iter().enumerate() {
+            let black = bitmap.get_pixel(x as u32, y as u32).0[0] == 0;
+            assert_eq!(black, *is_wall);
+        }
+    }
+}
+
+#[test]
+fn solve_observed_reports_bfs_layers() {
+    let mut maze = Maze::new(&size(3, 2));
+    maze.enable_all_walls();
+    for (a, b) in [
+        ((0, 0), (1, 0)),
+        ((1, 0), (2, 0)),
+        ((1, 0), (1, 1)),
+        ((0, 0), (0, 1)),
+        ((2, 0), (2, 1)),
+    ]
+    .iter()
+    {
+        maze.connect(*a, *b).unwrap();
+    }
+
+    let mut visits = Vec::new();
+    let path = maze.solve_observed((1, 0), (0, 1), |coord, distance| {
+        visits.push((coord, distance))
+    });
+
+    assert_eq!(
+        visits,
+        vec![
+            ((1, 0), 0),
+            ((2, 0), 1),
+            ((1, 1), 1),
+            ((0, 0), 1),
+            ((2, 1), 2),
+            ((0, 1), 2),
+        ]
+    );
+    assert_eq!(path, maze.solve_bfs((1, 0), (0, 1)));
+}
+
+#[test]
+fn complete_keeps_the_sketch_and_connects_the_rest() {
+    let mut base = Maze::new(&size(8, 6));
+    base.enable_all_walls();
+    for x in 1..6 {
+        base.connect((x, 2), (x + 1, 2)).unwrap();
+    }
+    for y in 0..5 {
+        base.connect((7, y), (7, y + 1)).unwrap();
+    }
+    base.connect((0, 5), (1, 5)).unwrap();
+    let sketch: Vec<(Coord, Coord)> = base.passages().collect();
+
+    let mut generator = MazeGen::new(&base.size);
+    generator.complete(&base, 9);
+    let maze = &generator.maze;
+
+    for (a, b) in &sketch {
+        assert!(maze.get_open_neighbor_coords(*a).contains(b));
+    }
+    assert_eq!(maze.reachable_count((0, 0)), 8 * 6);
+    // the sketch had no loops, so neither does the result
+    assert!(maze.is_perfect());
+}
+
+#[test]
+fn to_svg_animated_solution_draws_the_whole_path() {
+    let maze = gen_maze_seeded(&size(5, 4), 3);
+    let path = maze.solve_bfs((0, 0), (4, 3)).unwrap();
+    let svg = maze.to_svg_animated_solution((0, 0), (4, 3));
+
+    assert!(svg.starts_with("<svg"));
+    // cell centers on a 20 unit grid, shifted by the wall thickness of 2
+    let points: Vec<String> = path
+        .iter()
+        .map(|(x, y)| format!("{} {}", 2 + x * 20 + 10, 2 + y * 20 + 10))
+        .collect();
+    assert!(svg.contains(&format!("<path d=\"M{}\"", points.join(" L"))));
+
+    let length = (path.len() - 1) * 20;
+    assert!(svg.contains(&format!(
+        "stroke-dasharray=\"{len}\" stroke-dashoffset=\"{len}\"",
+        len = length
+    )));
+    assert_eq!(svg.matches("<animate ").count(), 1);
+    assert!(svg.contains(&format!(
+        "<animate attributeName=\"stroke-dashoffset\" from=\"{}\" to=\"0\"",
+        length
+    )));
+
+    // no solution, no path
+    let mut walled = Maze::new(&size(3, 3));
+    walled.enable_all_walls();
+    let svg = walled.to_svg_animated_solution((0, 0), (2, 2));
+    assert!(!svg.contains("<path") && !svg.contains("<animate"));
+}
+
+#[test]
+fn wall_rects_merged_covers_a_border_side_with_one_rect() {
+    let maze = gen_maze_seeded(&size(6, 4), 2);
+    let rects = maze.wall_rects_merged(10.0);
+
+    // the entrance and exit are on the north and south borders
+    assert!(rects.contains(&(0.0, 0.0, 0.0, 40.0)));
+    assert!(rects.contains(&(60.0, 0.0, 0.0, 40.0)));
+    assert!(rects.iter().all(|rect| rect.2 == 0.0 || rect.3 == 0.0));
+
+    // the runs add up to every enabled wall, shared walls counted once
+    let wall_count = maze
+        .iter_cells()
+        .map(|cell| {
+            let (x, y) = cell.coord;
+            let mut count = 0;
+            count += maze.is_wall_enabled((x, y), &TileDirection::NORTH) as usize;
+            count += maze.is_wall_enabled((x, y), &TileDirection::WEST) as usize;
+            if x == 5 {
+                count += maze.is_wall_enabled((x, y), &TileDirection::EAST) as usize;
+            }
+            if y == 3 {
+                count += maze.is_wall_enabled((x, y), &TileDirection::SOUTH) as usize;
+            }
+            count
+        })
+        .sum::<usize>();
+    let total: f64 = rects.iter().map(|rect| rect.2 + rect.3).sum();
+    assert_eq!(total, wall_count as f64 * 10.0);
+
+    // with every wall up each grid line is a single run
+    let mut walled = Maze::new(&size(6, 4));
+    walled.enable_all_walls();
+    assert_eq!(walled.wall_rects_merged(10.0).len(), (4 + 1) + (6 + 1));
+}
+
+#[test]
+fn gen_maze_split_seed_keeps_the_layout() {
+    let layout = gen_maze_seeded(&size(10, 8), 21);
+    let carved: Vec<(Coord, Coord)> = layout.passages().collect();
+
+    let variants: Vec<Maze> = (0..6)
+        .map(|variation| gen_maze_split_seed(&size(10, 8), 21, variation))
+        .collect();
+    for maze in &variants {
+        // braiding only adds passages on top of the carved ones
+        for (a, b) in &carved {
+            assert!(maze.get_open_neighbor_coords(*a).contains(b));
+        }
+        assert_eq!(maze.entrance.1, 0);
+        assert_eq!(maze.exit.1, 7);
+        assert!(maze.is_solvable(maze.entrance, maze.exit));
+    }
+    assert!(variants[1..].iter().any(|maze| maze != &variants[0]));
+    assert!(variants
+        .iter()
+        .any(|maze| maze.passages().count() > carved.len()));
+
+    assert!(gen_maze_split_seed(&size(10, 8), 21, 3) == variants[3]);
+    assert!(gen_maze_split_seed(&size(10, 8), 22, 3) != variants[3]);
+}
+
+#[test]
+fn generate_seeded_is_reproducible() {
+    let mut a = MazeGen::new(&size(9, 7));
+    let mut b = MazeGen::new(&size(9, 7));
+    a.generate_seeded(42);
+    b.generate_seeded(42);
+    assert!(a.maze == b.maze);
+    assert!(gen_maze_seeded(&size(9, 7), 42) == a.maze);
+
+    b.generate_seeded(43);
+    assert!(a.maze != b.maze);
+}
+
+#[test]
+fn generate_with_rng_draws_from_the_callers_stream() {
+    let mut rng = Pcg64::seed_from_u64(5);
+    let mut generator = MazeGen::new(&size(8, 8));
+    generator.generate_with_rng(&mut rng);
+    let first = generator.maze.clone();
+    assert!(first.is_perfect());
+
+    // the stream moved on, so the next maze differs
+    generator.generate_with_rng(&mut rng);
+    assert!(generator.maze.is_perfect());
+    assert!(generator.maze != first);
+
+    let mut replayed = Pcg64::seed_from_u64(5);
+    generator.generate_with_rng(&mut replayed);
+    assert!(generator.maze == first);
+}
+
+#[test]
+fn new_walls_the_perimeter_of_non_square_mazes() {
+    let maze = Maze::new(&size(3, 5));
+
+    for cell in maze.iter_cells() {
+        let (x, y) = cell.coord;
+        let edges = [
+            (TileDirection::NORTH, y == 0),
+            (TileDirection::EAST, x == 2),
+            (TileDirection::SOUTH, y == 4),
+            (TileDirection::WEST, x == 0),
+        ];
+        for (dir, on_edge) in edges.iter() {
+            let is_opening = (cell.coord == (0, 0) && *dir == TileDirection::NORTH)
+                || (cell.coord == (2, 4) && *dir == TileDirection::SOUTH);
+            assert_eq!(cell.is_wall_enabled(dir), *on_edge && !is_opening);
+        }
+    }
+}
+
+// 3x3 serpentine: right along the top row, left along the middle, right along the bottom
+fn serpentine() -> Maze {
+    let mut maze = Maze::new(&size(3, 3));
+    maze.enable_all_walls();
+    for (a, b) in [
+        ((0, 0), (1, 0)),
+        ((1, 0), (2, 0)),
+        ((2, 0), (2, 1)),
+        ((2, 1), (1, 1)),
+        ((1, 1), (0, 1)),
+        ((0, 1), (0, 2)),
+        ((0, 2), (1, 2)),
+        ((1, 2), (2, 2)),
+    ]
+    .iter()
+    {
+        maze.connect(*a, *b).unwrap();
+    }
+    maze
+}
+
+#[test]
+fn solve_bfs_follows_open_passages_only() {
+    let mut maze = serpentine();
+    assert_eq!(
+        maze.solve_bfs((0, 0), (2, 2)),
+        Some(vec![
+            (0, 0),
+            (1, 0),
+            (2, 0),
+            (2, 1),
+            (1, 1),
+            (0, 1),
+            (0, 2),
+            (1, 2),
+            (2, 2)
+        ])
+    );
+    assert_eq!(maze.solve_bfs((1, 1), (1, 1)), Some(vec![(1, 1)]));
+
+    // a shortcut is taken once opened
+    maze.connect((1, 1), (1, 2)).unwrap();
+    assert_eq!(maze.solve_bfs((0, 0), (2, 2)).unwrap().len(), 7);
+
+    maze.disconnect((0, 1), (0, 2)).unwrap();
+    maze.disconnect((1, 1), (1, 2)).unwrap();
+    assert_eq!(maze.solve_bfs((0, 0), (2, 2)), None);
+}
+
+#[test]
+fn solve_astar_matches_bfs() {
+    let mut maze = gen_maze_seeded(&size(15, 12), 8);
+    maze.braid(8, 0.6);
+
+    let coords: Vec<Coord> = maze.iter_cells().map(|cell| cell.coord).collect();
+    for start in coords.iter().step_by(11) {
+        for goal in coords.iter().step_by(13) {
+            let bfs = maze.solve_bfs(*start, *goal).unwrap();
+            let astar = maze.solve_astar(*start, *goal).unwrap();
+            assert_eq!(astar.len(), bfs.len());
+            assert_eq!((astar[0], astar[astar.len() - 1]), (*start, *goal));
+            assert!(is_open_path(&maze, &astar));
+        }
+    }
+
+    let mut walled = Maze::new(&size(3, 3));
+    walled.enable_all_walls();
+    assert_eq!(walled.solve_astar((0, 0), (2, 2)), None);
+}
+
+#[test]
+fn distances_grow_by_one_per_passage() {
+    let maze = gen_maze_seeded(&size(10, 7), 4);
+    let source = (3, 2);
+    let distances = maze.distances(source);
+
+    assert_eq!(distances[source.0][source.1], Some(0));
+    for (a, b) in maze.passages() {
+        let (da, db) = (distances[a.0][a.1].unwrap(), distances[b.0][b.1].unwrap());
+        // a tree, so neighbors are never at the same distance
+        assert_eq!(da.max(db) - da.min(db), 1);
+    }
+    assert_eq!(
+        distances[9][6].unwrap() + 1,
+        maze.solve_bfs(source, (9, 6)).unwrap().len()
+    );
+
+    let mut walled = Maze::new(&size(2, 2));
+    walled.enable_all_walls();
+    walled.connect((0, 0), (1, 0)).unwrap();
+    let distances = walled.distances((0, 0));
+    assert_eq!(distances[1][0], Some(1));
+    assert_eq!(distances[0][1], None);
+}
+
+#[test]
+fn generate_prim_reaches_every_cell() {
+    let mut generator = MazeGen::new(&size(12, 9));
+    generator.generate_prim(3);
+
+    assert_eq!(generator.maze.reachable_count((0, 0)), 12 * 9);
+    assert!(generator.maze.is_perfect());
+}
+
+#[test]
+fn generate_kruskal_carves_a_spanning_tree() {
+    let mut generator = MazeGen::new(&size(11, 6));
+    generator.generate_kruskal(3);
+
+    assert_eq!(generator.maze.passages().count(), 11 * 6 - 1);
+    assert!(generator.maze.is_perfect());
+}
+
+#[test]
+fn generate_wilson_connects_every_cell() {
+    let mut generator = MazeGen::new(&size(10, 8));
+    generator.generate_wilson(3);
+    assert_eq!(generator.maze.reachable_count((5, 5)), 10 * 8);
+    assert!(generator.maze.is_perfect());
+
+    let mut single = MazeGen::new(&size(1, 1));
+    single.generate_wilson(3);
+    assert_eq!(single.maze.passages().count(), 0);
+}
+
+#[test]
+fn generate_aldous_broder_visits_every_cell() {
+    let mut generator = MazeGen::new(&size(10, 10));
+    generator.generate_aldous_broder(3);
+
+    assert!(generator
+        .maze
+        .iter_cells()
+        .all(|cell| generator.maze.passage_degree(cell.coord) > 0));
+    assert_eq!(generator.maze.reachable_count((0, 0)), 10 * 10);
+    assert!(generator.maze.is_perfect());
+}
+
+#[test]
+fn generate_binary_tree_has_its_signature() {
+    let mut generator = MazeGen::new(&size(9, 7));
+    generator.generate_binary_tree(3);
+    let maze = &generator.maze;
+
+    // nothing to carve north on the top row, nothing east on the last column
+    for i in 0..8 {
+        assert!(!maze.is_wall_enabled((i, 0), &TileDirection::EAST));
+    }
+    for j in 1..7 {
+        assert!(!maze.is_wall_enabled((8, j), &TileDirection::NORTH));
+    }
+    assert!(maze.is_perfect());
+}
+
+#[test]
+fn generate_sidewinder_opens_the_top_row() {
+    for &(width, height) in [(9, 6), (3, 12), (1, 5)].iter() {
+        let mut generator = MazeGen::new(&size(width, height));
+        generator.generate_sidewinder(3);
+        let maze = &generator.maze;
+
+        assert!(maze.is_perfect());
+        for i in 0..width - 1 {
+            assert!(!maze.is_wall_enabled((i, 0), &TileDirection::EAST));
+        }
+    }
+}
+
+#[test]
+fn generate_eller_is_perfect_on_a_tall_grid() {
+    let mut generator = MazeGen::new(&size(5, 100));
+    generator.generate_eller(3);
+
+    assert!(generator.maze.is_perfect());
+    assert_eq!(generator.maze.passages().count(), 5 * 100 - 1);
+}
+
+#[test]
+fn generate_recursive_division_is_perfect() {
+    for &(width, height) in [(10, 10), (13, 4), (1, 6)].iter() {
+        let mut generator = MazeGen::new(&size(width, height));
+        generator.generate_recursive_division(3);
+        assert!(generator.maze.is_perfect());
+    }
+}
+
+#[test]
+fn generate_hunt_and_kill_covers_the_grid() {
+    let mut generator = MazeGen::new(&size(14, 9));
+    generator.generate_hunt_and_kill(3);
+
+    assert!(generator
+        .maze
+        .iter_cells()
+        .all(|cell| generator.maze.passage_degree(cell.coord) > 0));
+    assert_eq!(generator.maze.reachable_count((13, 8)), 14 * 9);
+    assert!(generator.maze.is_perfect());
+}
+
+#[test]
+fn generate_growing_tree_newest_is_the_backtracker() {
+    let mut backtracker = MazeGen::new(&size(10, 8));
+    let mut growing = MazeGen::new(&size(10, 8));
+    for seed in 0..5 {
+        backtracker.generate_seeded(seed);
+        growing.generate_growing_tree(seed, CellSelection::Newest);
+        assert!(growing.maze == backtracker.maze);
+    }
+
+    for strategy in [CellSelection::Random, CellSelection::Oldest].iter() {
+        growing.generate_growing_tree(1, *strategy);
+        assert!(growing.maze.is_perfect());
+    }
+}
+
+#[test]
+fn braid_removes_dead_ends() {
+    let mut maze = gen_maze_seeded(&size(12, 12), 5);
+    let before = maze.dead_ends().len();
+    assert!(before > 10);
+
+    let mut half = maze.clone();
+    half.braid(5, 0.5);
+    let after_half = half.dead_ends().len();
+    assert!(after_half < before && after_half > 0);
+
+    maze.braid(5, 1.0);
+    assert!(maze.dead_ends().is_empty());
+    assert_eq!(maze.reachable_count((0, 0)), 12 * 12);
+}
+
+#[test]
+fn dead_ends_on_a_hand_built_maze() {
+    // the serpentine only dead-ends at its two ends
+    let mut maze = serpentine();
+    assert_eq!(maze.dead_ends(), vec![(0, 0), (2, 2)]);
+
+    // a branch off the middle row adds a third, the entrance and exit don't count
+    maze.disconnect((1, 2), (2, 2)).unwrap();
+    maze.connect((2, 1), (2, 2)).unwrap();
+    assert_eq!(maze.dead_ends(), vec![(0, 0), (1, 2), (2, 2)]);
+
+    maze.connect((1, 2), (2, 2)).unwrap();
+    maze.connect((0, 0), (0, 1)).unwrap();
+    assert!(maze.dead_ends().is_empty());
+}
+
+#[test]
+fn toroidal_mazes_wrap_across_the_seams() {
+    let mut maze = Maze::new_with_topology(&size(4, 3), Topology::Toroidal);
+    maze.enable_all_walls();
+
+    // the wrapped neighbors share their walls
+    assert!(maze.connect((3, 1), (0, 1)).is_ok());
+    assert!(!maze.is_wall_enabled((3, 1), &TileDirection::EAST));
+    assert!(!maze.is_wall_enabled((0, 1), &TileDirection::WEST));
+    assert!(maze.connect((2, 0), (2, 2)).is_ok());
+    assert!(!maze.is_wall_enabled((2, 2), &TileDirection::SOUTH));
+    assert_eq!(maze.solve_bfs((3, 1), (0, 1)), Some(vec![(3, 1), (0, 1)]));
+    assert!(maze.connect((3, 1), (1, 1)).is_err());
+
+    // generators make use of them
+    let mut generator = MazeGen::new_with_topology(&size(8, 8), Topology::Toroidal);
+    generator.generate_seeded(2);
+    assert!(generator.maze.is_perfect());
+    assert!((0..8).any(|j| !generator.maze.is_wall_enabled((7, j), &TileDirection::EAST)));
+}
+
+#[test]
+fn recursive_division_keeps_toroidal_seams_closed() {
+    for seed in 0..5 {
+        let mut generator = MazeGen::new_with_topology(&size(7, 5), Topology::Toroidal);
+        generator.generate_recursive_division(seed);
+        assert!(generator.maze.is_perfect());
+
+        let built = MazeBuilder::new()
+            .size(size(6, 6))
+            .topology(Topology::Toroidal)
+            .algorithm(Algorithm::RecursiveDivision)
+            .seed(seed)
+            .build()
+            .unwrap();
+        assert!(built.is_perfect());
+    }
+}
+
+#[test]
+fn to_svg_draws_one_line_per_wall() {
+    // 24 walls on a 3x3 grid, minus 8 passages, the entrance and the exit
+    let maze = serpentine();
+    let svg = maze.to_svg(10.0, 2.0);
+
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert_eq!(svg_lines(&svg).len(), 24 - 8 - 2);
+    assert!(svg.contains("width=\"32\" height=\"32\""));
+    // the wall under (0, 0), shifted by half the wall thickness
+    assert!(svg_lines(&svg).contains(&(1.0, 11.0, 11.0, 11.0)));
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn to_image_has_the_documented_size_and_black_borders() {
+    let maze = gen_maze_seeded(&size(5, 3), 2);
+    let img = maze.to_image(10, 2);
+    let black = image::Rgba([0, 0, 0, 255]);
+    let white = image::Rgba([255, 255, 255, 255]);
+
+    assert_eq!(img.dimensions(), (5 * 10 + 2, 3 * 10 + 2));
+    for y in 0..32 {
+        assert_eq!(*img.get_pixel(0, y), black);
+        assert_eq!(*img.get_pixel(51, y), black);
+    }
+    // the top border is only open at the entrance above (0, 0)
+    for x in 0..52 {
+        let expected = if (2..10).contains(&x) { white } else { black };
+        assert_eq!(*img.get_pixel(x, 0), expected);
+    }
+    assert_eq!(*img.get_pixel(45, 31), white);
+    assert_eq!(*img.get_pixel(25, 31), black);
+}
+
+#[test]
+fn set_entrance_carves_the_outer_wall() {
+    let mut maze = gen_maze_seeded(&size(5, 4), 1);
+    assert!(!maze.is_wall_enabled((0, 0), &TileDirection::NORTH));
+    assert!(!maze.is_wall_enabled((4, 3), &TileDirection::SOUTH));
+
+    maze.set_entrance((0, 2), &TileDirection::WEST);
+    maze.set_exit((4, 1), &TileDirection::EAST);
+    assert_eq!((maze.entrance, maze.exit), ((0, 2), (4, 1)));
+    assert!(!maze.is_wall_enabled((0, 2), &TileDirection::WEST));
+    assert!(!maze.is_wall_enabled((4, 1), &TileDirection::EAST));
+    // the previous openings are sealed again
+    assert!(maze.is_wall_enabled((0, 0), &TileDirection::NORTH));
+    assert!(maze.is_wall_enabled((4, 3), &TileDirection::SOUTH));
+}
+
+#[test]
+#[should_panic(expected = "openings must be carved in an outer wall")]
+fn set_entrance_rejects_inner_walls() {
+    let mut maze = Maze::new(&size(4, 4));
+    maze.set_entrance((1, 1), &TileDirection::NORTH);
+}
+
+#[test]
+fn render_with_path_marks_the_listed_cells() {
+    let maze = serpentine();
+    let rendered = maze.render_with_path(&[(0, 0), (1, 0), (2, 0)]);
+    let lines: Vec<&str> = rendered.lines().collect();
+
+    assert_eq!(lines[1], "|* * *|");
+    assert_eq!(rendered.matches('*').count(), 3);
+
+    // cells don't need to be connected
+    let rendered = maze.render_with_path(&[(0, 0), (2, 2)]);
+    let lines: Vec<&str> = rendered.lines().collect();
+    assert!(lines[1].starts_with("|*"));
+    assert!(lines[5].ends_with("*|"));
+    assert_eq!(rendered.matches('*').count(), 2);
+}
+
+#[test]
+fn index_returns_the_cell_at_a_coord() {
+    let mut maze = Maze::new(&size(3, 3));
+    assert_eq!(maze[(1, 1)].coord, (1, 1));
+    assert_eq!(maze[(2, 0)].coord, (2, 0));
+
+    maze[(1, 1)].enable_wall(&TileDirection::EAST);
+    assert!(maze[(1, 1)].is_wall_enabled(&TileDirection::EAST));
+}
+
+#[test]
+#[should_panic(expected = "coord (3, 1) is out of bounds for a 3x3 maze")]
+fn index_panics_out_of_bounds() {
+    let maze = Maze::new(&size(3, 3));
+    let _ = &maze[(3, 1)];
+}
+
+#[test]
+fn iter_cells_covers_the_grid_column_by_column() {
+    let mut maze = Maze::new_with_data(&size(4, 3), Topology::Planar, |_| 0);
+    let coords: Vec<Coord> = maze.iter_cells().map(|cell| cell.coord).collect();
+
+    assert_eq!(coords.len(), 4 * 3);
+    assert_eq!(&coords[..4], &[(0, 0), (0, 1), (0, 2), (1, 0)]);
+    let unique: BTreeSet<Coord> = coords.iter().copied().collect();
+    assert_eq!(unique.len(), 4 * 3);
+
+    for cell in maze.iter_cells_mut() {
+        cell.data = cell.coord.0 + cell.coord.1;
+    }
+    assert_eq!(maze.iter_cells().filter(|cell| cell.data >= 4).count(), 3);
+}
+
+#[test]
+fn passages_list_each_open_pair_once() {
+    let maze = gen_maze_seeded(&size(9, 6), 7);
+    let passages: Vec<(Coord, Coord)> = maze.passages().collect();
+    assert_eq!(passages.len(), 9 * 6 - 1);
+
+    let unordered: BTreeSet<(Coord, Coord)> = passages
+        .iter()
+        .map(|(a, b)| ((*a).min(*b), (*a).max(*b)))
+        .collect();
+    assert_eq!(unordered.len(), passages.len());
+    for (a, b) in &passages {
+        assert!(maze.get_open_neighbor_coords(*a).contains(b));
+    }
+
+    assert_eq!(serpentine().passages().count(), 8);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn from_ascii_round_trips_display() {
+    for &(width, height) in [(6, 4), (1, 3), (4, 1)].iter() {
+        let maze = gen_maze_seeded(&size(width, height), 3);
+        let parsed = Maze::from_ascii(&maze.to_string()).unwrap();
+        assert!(parsed == maze);
+    }
+}
+
+#[test]
+fn from_ascii_reports_malformed_input() {
+    assert_eq!(Maze::from_ascii("").err(), Some(ParseError::Empty));
+    assert_eq!(
+        Maze::from_ascii(" __\n|  |").err(),
+        Some(ParseError::BadHeader)
+    );
+    assert_eq!(
+        Maze::from_ascii(" ___\n|   |\n|###|").err(),
+        Some(ParseError::MissingLine)
+    );
+    assert_eq!(
+        Maze::from_ascii(" ___\n|  |").err(),
+        Some(ParseError::RaggedLine {
+            line: 1,
+            expected: 5,
+            found: 4
+        })
+    );
+    assert_eq!(
+        Maze::from_ascii(" ___\n| x |").err(),
+        Some(ParseError::UnexpectedChar {
+            line: 1,
+            column: 2,
+            found: 'x'
+        })
+    );
+
+    let maze = Maze::from_ascii(" ___\n|   |").unwrap();
+    assert_eq!((maze.size.width, maze.size.height), (2, 1));
+    assert!(!maze.is_wall_enabled((0, 0), &TileDirection::EAST));
+}
+
+// largest distance from `coord` to any reachable cell
+fn eccentricity(maze: &Maze, coord: Coord) -> usize {
+    maze.distances(coord)
+        .iter()
+        .flatten()
+        .filter_map(|distance| *distance)
+        .max()
+        .unwrap()
+}
+
+#[test]
+fn longest_path_joins_mutually_farthest_cells() {
+    let maze = gen_maze_seeded(&size(11, 9), 12);
+    let path = maze.longest_path();
+    let (start, end) = (path[0], path[path.len() - 1]);
+    let length = path.len() - 1;
+
+    assert!(is_open_path(&maze, &path));
+    assert_eq!(maze.distances(start)[end.0][end.1], Some(length));
+    assert_eq!(eccentricity(&maze, start), length);
+    assert_eq!(eccentricity(&maze, end), length);
+    // no pair of cells is farther apart
+    assert!(maze
+        .iter_cells()
+        .all(|cell| eccentricity(&maze, cell.coord) <= length));
+}
+
+#[test]
+fn stepping_to_the_end_matches_generate_seeded() {
+    let mut stepped = MazeGen::new(&size(9, 7));
+    stepped.start(17);
+    let mut steps = 0;
+    while stepped.step() {
+        steps += 1;
+    }
+    assert!(!stepped.step());
+    // one step per carved passage and one per backtrack
+    assert_eq!(steps + 1, 2 * (9 * 7 - 1));
+
+    let mut generated = MazeGen::new(&size(9, 7));
+    generated.generate_seeded(17);
+    assert!(stepped.maze == generated.maze);
+}
+
+// a 7x7 grid without its (0,0) corner and its 3x3 center
+fn ring_mask(coord: Coord) -> bool {
+    let hole = (2..=4).contains(&coord.0) && (2..=4).contains(&coord.1);
+    coord != (0, 0) && !hole
+}
+
+fn assert_carves_ring(maze: &Maze) {
+    for cell in maze.iter_cells() {
+        if !ring_mask(cell.coord) {
+            assert_eq!(maze.passage_degree(cell.coord), 0, "{:?}", cell.coord);
+        }
+    }
+    assert_eq!(maze.reachable_count((1, 0)), 7 * 7 - 10);
+}
+
+#[test]
+fn every_generator_honors_masks() {
+    let generators: Vec<fn(&mut MazeGen)> = vec![
+        |gen| gen.generate_seeded(3),
+        |gen| gen.generate_prim(3),
+        |gen| gen.generate_kruskal(3),
+        |gen| gen.generate_wilson(3),
+        |gen| gen.generate_aldous_broder(3),
+        |gen| gen.generate_hunt_and_kill(3),
+        |gen| gen.generate_growing_tree(3, CellSelection::Random),
+        |gen| {
+            gen.generate_zoned(
+                3,
+                vec![(
+                    Rect {
+                        x: 0,
+                        y: 0,
+                        width: 7,
+                        height: 7,
+                    },
+                    1.0,
+                )],
+            )
+        },
+    ];
+    for generate in generators {
+        let mut generator = MazeGen::new_masked(&size(7, 7), &ring_mask);
+        generate(&mut generator);
+        assert_carves_ring(&generator.maze);
+    }
+
+    let mut generator = MazeGen::new_masked(&size(7, 7), &ring_mask);
+    generator.generate_regions(3, 4);
+    for cell in generator.maze.iter_cells() {
+        if !ring_mask(cell.coord) {
+            assert_eq!(generator.maze.passage_degree(cell.coord), 0);
+        }
+    }
+
+    // a base corridor running into the hole stops at its edge
+    let mut base = Maze::new(&size(7, 7));
+    base.enable_all_walls();
+    base.connect((1, 0), (1, 1)).unwrap();
+    base.connect((1, 1), (1, 2)).unwrap();
+    base.connect((1, 2), (2, 2)).unwrap();
+    let mut generator = MazeGen::new_masked(&size(7, 7), &ring_mask);
+    generator.complete(&base, 3);
+    assert!(!generator.maze.is_reachable((1, 0), (3, 3)));
+    assert_eq!(generator.maze.passage_degree((3, 3)), 0);
+    assert_eq!(generator.maze.reachable_count((1, 0)), 7 * 7 - 10 + 1);
+}
+
+#[test]
+#[should_panic(expected = "can't generate a masked maze")]
+fn grid_generators_reject_masks() {
+    let mut generator = MazeGen::new_masked(&size(7, 7), &ring_mask);
+    generator.generate_sidewinder(3);
+}
+
+#[test]
+fn weighted_corner_gets_shorter_distances() {
+    let corner = |(i, j): Coord| i < 8 && j < 8;
+    let corner_distance = |maze: &Maze| -> usize {
+        let distances = maze.distances((0, 0));
+        (0..8)
+            .flat_map(|i| (0..8).map(move |j| (i, j)))
+            .map(|(i, j)| distances[i][j].unwrap())
+            .sum()
+    };
+
+    for seed in 0..4 {
+        let mut uniform = MazeGen::new(&size(20, 20));
+        uniform.generate_weighted(seed, |_, _| 1.0);
+        let mut weighted = MazeGen::new(&size(20, 20));
+        weighted.generate_weighted(seed, |a, b| if corner(a) && corner(b) { 0.01 } else { 1.0 });
+
+        assert!(corner_distance(&weighted.maze) < corner_distance(&uniform.maze));
+    }
+}
+
+#[test]
+fn maze_3d_is_reachable_from_origin() {
+    let maze = gen_maze_3d_seeded(6, 5, 4, 8);
+
+    let mut seen = BTreeSet::new();
+    let mut stack = vec![(0, 0, 0)];
+    seen.insert((0, 0, 0));
+    while let Some(coord) = stack.pop() {
+        for next in maze.get_open_neighbor_coords(coord) {
+            if seen.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+    assert_eq!(seen.len(), 6 * 5 * 4);
+}
+
+#[test]
+fn hex_maze_is_connected() {
+    let maze = gen_hex_maze_seeded(4, 2);
+    // 1 + 6 + 12 + 18 + 24 cells
+    assert_eq!(maze.cell_count(), 61);
+
+    let mut seen = BTreeSet::new();
+    let mut stack = vec![(0, 0)];
+    seen.insert((0, 0));
+    while let Some(coord) = stack.pop() {
+        for next in maze.get_open_neighbor_coords(coord) {
+            if seen.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+    assert_eq!(seen.len(), maze.cell_count());
+
+    for coord in maze.coords() {
+        let neighbors = maze.get_neighbor_coords_and_dirs(coord);
+        assert!(neighbors.len() <= 6);
+        assert!(neighbors.iter().all(|(next, _)| maze.is_valid_coord(next)));
+    }
+    // the center has all 6 neighbors, a corner only 3
+    assert_eq!(maze.get_neighbor_coords_and_dirs((0, 0)).len(), 6);
+    assert_eq!(maze.get_neighbor_coords_and_dirs((4, -4)).len(), 3);
+}
+
+#[test]
+fn wall_bits_behave_like_a_set() {
+    let mut rng = Pcg64::seed_from_u64(4);
+    let mut cell = Cell::new((0, 0));
+    let mut model = BTreeSet::new();
+    for _ in 0..200 {
+        let dir = **ALL_TILE_DIRECTIONS.choose(&mut rng).unwrap();
+        if rng.gen_bool(0.5) {
+            cell.enable_wall(&dir);
+            model.insert(dir);
+        } else {
+            cell.disable_wall(&dir);
+            model.remove(&dir);
+        }
+        for dir in ALL_TILE_DIRECTIONS.iter() {
+            assert_eq!(cell.is_wall_enabled(dir), model.contains(*dir));
+        }
+        assert_eq!(cell.open_wall_count(), 4 - model.len());
+    }
+
+    // both sides of every inner wall agree on a generated maze
+    let maze = gen_maze_seeded(&size(9, 7), 6);
+    for cell in maze.iter_cells() {
+        for (next, dir) in maze.get_neighbor_coords_and_dirs(cell.coord) {
+            let back = maze.get_dir_between(next, cell.coord).unwrap();
+            assert_eq!(
+                maze.is_wall_enabled(cell.coord, &dir),
+                maze.is_wall_enabled(next, &back)
+            );
+        }
+    }
+    assert_eq!(maze.passages().count(), 9 * 7 - 1);
+}
+
+#[test]
+fn clone_is_equal_until_mutated() {
+    let maze = gen_maze_seeded(&size(6, 6), 10);
+    let mut copy = maze.clone();
+    assert!(copy == maze);
+
+    let dir = ALL_TILE_DIRECTIONS
+        .iter()
+        .find(|dir| !copy.is_edge_wall((2, 2), dir) && copy.is_wall_enabled((2, 2), dir))
+        .unwrap();
+    copy.disable_wall((2, 2), dir);
+    assert!(copy != maze);
+    // the original is untouched
+    assert!(maze.is_wall_enabled((2, 2), dir));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn generate_returns_a_reproducible_seed() {
+    let mut generator = MazeGen::new(&size(8, 8));
+    let seed = generator.generate();
+    assert_eq!(generator.last_seed(), Some(seed));
+
+    let mut replay = MazeGen::new(&size(8, 8));
+    replay.generate_seeded(seed);
+    assert!(replay.maze == generator.maze);
+}
+
+#[test]
+fn recorded_events_rebuild_the_maze() {
+    let mut generator = MazeGen::new(&size(7, 5));
+    let events = generator.generate_recorded(12);
+
+    let carves = events
+        .iter()
+        .filter(|event| matches!(event, GenEvent::Carve { .. }))
+        .count();
+    assert_eq!(carves, 7 * 5 - 1);
+
+    let mut rebuilt = Maze::new(&size(7, 5));
+    rebuilt.enable_all_walls();
+    rebuilt.replay(&events);
+    assert!(rebuilt == generator.maze);
+}
+
+#[test]
+fn generate_from_checks_the_start() {
+    let mut generator = MazeGen::new(&size(6, 4));
+    assert_eq!(
+        generator.generate_from(1, (6, 0)),
+        Err(MazeError::OutOfBounds((6, 0)))
+    );
+    assert_eq!(
+        generator.generate_from(1, (0, 4)),
+        Err(MazeError::OutOfBounds((0, 4)))
+    );
+
+    generator.generate_from(1, (3, 2)).unwrap();
+    assert_eq!(generator.visit_order()[0], (3, 2));
+    assert_eq!(generator.maze.reachable_count((3, 2)), 6 * 4);
+    assert!(generator.maze.is_perfect());
+}
+
+#[test]
+fn generated_mazes_are_perfect_until_braided() {
+    let mut maze = gen_maze_seeded(&size(8, 8), 14);
+    assert!(maze.is_perfect());
+
+    maze.braid(14, 1.0);
+    assert!(maze.passages().count() > 8 * 8 - 1);
+    assert!(!maze.is_perfect());
+}
+
+#[test]
+fn count_shortest_paths_around_a_block() {
+    // a 2x2 ring: two routes of length 2 between opposite corners
+    let mut maze = Maze::new(&size(2, 2));
+    assert_eq!(maze.count_shortest_paths((0, 0), (1, 1)), 2);
+
+    maze.enable_all_walls();
+    maze.connect((0, 0), (1, 0)).unwrap();
+    maze.connect((1, 0), (1, 1)).unwrap();
+    assert_eq!(maze.count_shortest_paths((0, 0), (1, 1)), 1);
+    assert_eq!(maze.count_shortest_paths((0, 0), (0, 1)), 0);
+}
+
+#[test]
+fn dead_end_fill_matches_bfs() {
+    for seed in 0..5 {
+        let maze = gen_maze_seeded(&size(9, 7), seed);
+        let filled = maze.solve_dead_end_fill((0, 0), (8, 6)).unwrap();
+        let shortest = maze.solve_bfs((0, 0), (8, 6)).unwrap();
+        assert_eq!(filled.len(), shortest.len());
+        assert!(is_open_path(&maze, &filled));
+    }
+}
+
+#[test]
+fn wall_follower_reaches_the_goal() {
+    for seed in 0..5 {
+        let maze = gen_maze_seeded(&size(9, 7), seed);
+        let path = maze.solve_wall_follower((0, 0), (8, 6)).unwrap();
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&(8, 6)));
+        assert!(is_open_path(&maze, &path));
+        // it may wander into dead ends, never beat the shortest route
+        assert!(path.len() >= maze.solve_bfs((0, 0), (8, 6)).unwrap().len());
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn parallel_generation_is_connected_and_deterministic() {
+    let maze = gen_maze_parallel(&size(20, 9), 17, 4);
+    assert!(maze.is_perfect());
+    assert_eq!(maze.reachable_count((0, 0)), 20 * 9);
+
+    for _ in 0..3 {
+        assert!(gen_maze_parallel(&size(20, 9), 17, 4) == maze);
+    }
+    assert!(gen_maze_parallel(&size(20, 9), 18, 4) != maze);
+}
+
+#[test]
+fn connect_opens_both_sides() {
+    let mut maze = Maze::new(&size(4, 4));
+    maze.enable_all_walls();
+
+    maze.connect((1, 1), (1, 2)).unwrap();
+    assert!(!maze.is_wall_enabled((1, 1), &TileDirection::SOUTH));
+    assert!(!maze.is_wall_enabled((1, 2), &TileDirection::NORTH));
+
+    maze.disconnect((1, 2), (1, 1)).unwrap();
+    assert!(maze.is_wall_enabled((1, 1), &TileDirection::SOUTH));
+    assert!(maze.is_wall_enabled((1, 2), &TileDirection::NORTH));
+
+    assert_eq!(
+        maze.connect((1, 1), (2, 2)),
+        Err(ConnectError::NotAdjacent((1, 1), (2, 2)))
+    );
+    assert_eq!(
+        maze.connect((1, 1), (1, 1)),
+        Err(ConnectError::NotAdjacent((1, 1), (1, 1)))
+    );
+    assert_eq!(
+        maze.connect((3, 3), (4, 3)),
+        Err(ConnectError::OutOfBounds((4, 3)))
+    );
+}
+
+#[test]
+fn try_new_rejects_zero_dimensions() {
+    assert!(matches!(
+        Maze::try_new(&size(0, 5)),
+        Err(MazeError::ZeroDimension)
+    ));
+    assert!(matches!(
+        Maze::try_new(&size(5, 0)),
+        Err(MazeError::ZeroDimension)
+    ));
+
+    let maze = Maze::try_new(&size(5, 5)).ok().unwrap();
+    assert_eq!(maze.size, size(5, 5));
+    assert_eq!(maze.iter_cells().count(), 25);
+}
+
+#[test]
+fn try_wall_methods_reject_invalid_coords() {
+    let mut maze = gen_maze_seeded(&size(4, 3), 2);
+    let before = maze.clone();
+
+    assert_eq!(
+        maze.try_enable_wall((4, 0), &TileDirection::EAST),
+        Err(MazeError::OutOfBounds((4, 0)))
+    );
+    assert_eq!(
+        maze.try_disable_wall((0, 3), &TileDirection::NORTH),
+        Err(MazeError::OutOfBounds((0, 3)))
+    );
+    assert!(maze == before);
+
+    assert_eq!(maze.try_enable_wall((1, 1), &TileDirection::EAST), Ok(()));
+    assert!(maze.is_wall_enabled((2, 1), &TileDirection::WEST));
+}
+
+#[test]
+fn wall_grid_dimensions_and_corners() {
+    let maze = gen_maze_seeded(&size(5, 3), 7);
+    let grid = maze.to_wall_grid();
+
+    assert_eq!(grid.len(), 2 * 5 + 1);
+    assert!(grid.iter().all(|column| column.len() == 2 * 3 + 1));
+    assert!(grid[0][0] && grid[10][0] && grid[0][6] && grid[10][6]);
+    // cell centers are open, the entrance and exit too
+    assert!(!grid[1][1] && !grid[9][5]);
+    assert!(!grid[1][0] && !grid[9][6]);
+}
+
+#[test]
+fn wall_grid_round_trip() {
+    let maze = gen_maze_seeded(&size(6, 4), 11);
+    let parsed = Maze::from_wall_grid(&maze.to_wall_grid()).ok().unwrap();
+    assert!(parsed == maze);
+
+    let even = vec![vec![true; 8]; 13];
+    assert!(matches!(
+        Maze::from_wall_grid(&even),
+        Err(MazeError::BadGridDimensions)
+    ));
+    let mut ragged = maze.to_wall_grid();
+    ragged[3].pop();
+    assert!(matches!(
+        Maze::from_wall_grid(&ragged),
+        Err(MazeError::BadGridDimensions)
+    ));
+}
+
+#[test]
+fn cell_data_reads_back() {
+    let maze = Maze::new_with_data(&size(4, 3), Topology::Planar, |(i, j)| (10 * i + j) as u32);
+    let mut generator = MazeGen::from_maze(maze);
+    generator.generate_seeded(3);
+    let mut maze = generator.maze;
+
+    // generation leaves the payload alone
+    assert_eq!(maze.cell_data((3, 2)), Some(&32));
+    *maze.cell_data_mut((1, 2)).unwrap() += 100;
+    assert_eq!(maze.cell_data((1, 2)), Some(&112));
+    assert_eq!(maze.cell_data((4, 0)), None);
+    assert!(maze.cell_data_mut((0, 3)).is_none());
+}
+
+#[test]
+fn recursive_division_drops_an_unfinished_run() {
+    let mut generator = MazeGen::new(&size(6, 5));
+    generator.start(4);
+    for _ in 0..10 {
+        generator.step();
+    }
+    generator.generate_recursive_division(4);
+    assert!(generator.visit_order().is_empty());
+    // the interrupted backtracker doesn't resume into the divided maze
+    let divided = generator.maze.clone();
+    assert!(!generator.step());
+    assert!(generator.maze == divided);
+    assert!(divided.is_perfect());
+
+    generator.generate_seeded(4);
+    let visited: BTreeSet<Coord> = generator.visit_order().iter().copied().collect();
+    assert_eq!(generator.visit_order().len(), 6 * 5);
+    assert_eq!(visited.len(), 6 * 5);
+}
+
+#[test]
+fn builder_braids_a_prim_maze() {
+    let builder = MazeBuilder::new()
+        .size(size(10, 10))
+        .algorithm(Algorithm::Prim)
+        .seed(21);
+    let perfect = builder.clone().build().unwrap();
+    let braided = builder.braid(0.8).build().unwrap();
+
+    assert_eq!(braided.reachable_count((0, 0)), 10 * 10);
+    assert!(braided.dead_ends().len() < perfect.dead_ends().len());
+    assert!(braided.passages().count() > perfect.passages().count());
+}
+
+#[test]
+fn builder_rejects_inner_openings() {
+    let builder = MazeBuilder::new().size(size(4, 4));
+    assert!(matches!(
+        builder
+            .clone()
+            .entrance((1, 1), TileDirection::NORTH)
+            .build(),
+        Err(MazeError::NotOuterWall((1, 1), TileDirection::NORTH))
+    ));
+    assert!(matches!(
+        builder.clone().exit((3, 3), TileDirection::WEST).build(),
+        Err(MazeError::NotOuterWall((3, 3), TileDirection::WEST))
+    ));
+    assert!(matches!(
+        builder.clone().exit((4, 3), TileDirection::SOUTH).build(),
+        Err(MazeError::OutOfBounds((4, 3)))
+    ));
+    assert!(builder.exit((3, 1), TileDirection::EAST).build().is_ok());
+}
+
+#[test]
+fn resize_grows_with_a_sealed_border() {
+    let original = gen_maze_seeded(&size(3, 3), 5);
+    let mut maze = original.clone();
+    maze.resize(&size(5, 5));
+    assert_eq!(maze.size, size(5, 5));
+
+    for cell in original.iter_cells() {
+        for (next, dir) in original.get_neighbor_coords_and_dirs(cell.coord) {
+            assert_eq!(
+                maze.is_wall_enabled(cell.coord, &dir),
+                original.is_wall_enabled(cell.coord, &dir),
+                "{:?} {:?} -> {:?}",
+                cell.coord,
+                dir,
+                next
+            );
+        }
+    }
+
+    // the new cells are walled in, the old exit closed
+    assert!(maze.is_wall_enabled((2, 2), &TileDirection::SOUTH));
+    for coord in [(3, 0), (4, 4), (0, 3), (3, 3)].iter() {
+        assert_eq!(maze.passage_degree(*coord), 0);
+    }
+
+    for i in 0..5 {
+        assert_eq!(maze.is_wall_enabled((i, 0), &TileDirection::NORTH), i != 0);
+        assert_eq!(maze.is_wall_enabled((i, 4), &TileDirection::SOUTH), i != 4);
+        assert!(maze.is_wall_enabled((0, i), &TileDirection::WEST));
+        assert!(maze.is_wall_enabled((4, i), &TileDirection::EAST));
+    }
+}
+
+#[test]
+fn four_rotations_give_the_same_maze() {
+    let maze = gen_maze_seeded(&size(7, 4), 19);
+    let quarter = maze.rotate_90();
+    assert_eq!(quarter.size, size(4, 7));
+    assert!(quarter.is_perfect());
+    assert!(quarter != maze);
+
+    let full = quarter.rotate_90().rotate_90().rotate_90();
+    assert!(full == maze);
+}
+
+#[test]
+fn theta_maze_is_connected_from_the_center() {
+    let maze = gen_theta_maze_seeded(6, 4);
+    assert_eq!(maze.cells_in_ring(0), 1);
+    assert!(maze.cells_in_ring(5) > maze.cells_in_ring(1));
+
+    let mut seen = BTreeSet::new();
+    let mut stack = vec![(0, 0)];
+    seen.insert((0, 0));
+    while let Some(coord) = stack.pop() {
+        for next in maze.get_open_neighbor_coords(coord) {
+            if seen.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+    assert_eq!(seen.len(), maze.coords().len());
+}
+
+#[test]
+fn to_dot_has_one_edge_per_passage() {
+    let mut maze = gen_maze_seeded(&size(6, 5), 23);
+    maze.braid(23, 0.5);
+    let dot = maze.to_dot();
+
+    assert!(dot.starts_with("graph maze {"));
+    assert_eq!(dot.matches(" -- ").count(), maze.passages().count());
+    assert_eq!(dot.matches("[label=").count(), 6 * 5);
+    assert!(dot.contains("c5_4 [label=\"(5, 4)\"];"));
+}
+
+#[test]
+fn adjacency_is_symmetric() {
+    let maze = gen_maze_seeded(&size(8, 6), 25);
+    let adjacency = maze.adjacency();
+    assert_eq!(adjacency.len(), 8 * 6);
+
+    for (coord, neighbors) in &adjacency {
+        for next in neighbors {
+            assert!(adjacency[next].contains(coord));
+        }
+    }
+    let dead_ends = maze.dead_ends();
+    assert!(!dead_ends.is_empty());
+    for coord in dead_ends {
+        assert_eq!(adjacency[&coord].len(), 1);
+    }
+}
+
+#[test]
+fn passage_degrees_of_a_known_maze() {
+    // a plus sign around (1, 1), with (0, 0) hanging off its top arm
+    let mut maze = Maze::new(&size(3, 3));
+    maze.enable_all_walls();
+    for next in [(1, 0), (2, 1), (1, 2), (0, 1)].iter() {
+        maze.connect((1, 1), *next).unwrap();
+    }
+    maze.connect((1, 0), (0, 0)).unwrap();
+
+    assert_eq!(maze.passage_degree((1, 1)), 4);
+    assert_eq!(maze.passage_degree((1, 0)), 2);
+    // the entrance isn't a passage
+    assert_eq!(maze.passage_degree((0, 0)), 1);
+    assert_eq!(maze.passage_degree((0, 1)), 1);
+    assert_eq!(maze.passage_degree((2, 2)), 0);
+    assert_eq!(maze[(1, 1)].open_wall_count(), 4);
+    assert_eq!(maze[(2, 1)].open_wall_count(), 1);
+}
+
+#[test]
+fn unsealed_generation_leaves_the_perimeter_open() {
+    let mut generator = MazeGen::from_maze(Maze::new_with_border(&size(5, 4), false));
+    generator.generate_seeded(27);
+    let maze = generator.maze;
+    assert!(!maze.is_border_sealed());
+    assert!(!maze.is_wall_enabled((2, 0), &TileDirection::NORTH));
+    assert!(!maze.is_wall_enabled((4, 2), &TileDirection::EAST));
+
+    let mut sealed = MazeGen::new(&size(5, 4));
+    sealed.generate_seeded(27);
+    assert!(sealed.maze.is_wall_enabled((2, 0), &TileDirection::NORTH));
+    assert!(sealed.maze.is_wall_enabled((4, 2), &TileDirection::EAST));
+}
+
+#[test]
+fn generate_steps_resumes_and_keeps_finished_runs() {
+    let mut generator = MazeGen::new(&size(6, 6));
+    assert!(!generator.generate_steps(30, 5));
+    assert!(generator.visit_order().len() < 6 * 6);
+
+    while !generator.generate_steps(30, 5) {}
+    let mut reference = MazeGen::new(&size(6, 6));
+    reference.generate_seeded(30);
+    assert!(generator.maze == reference.maze);
+
+    // a finished run is kept rather than restarted
+    assert!(generator.generate_steps(30, 0));
+    assert_eq!(generator.visit_order().len(), 6 * 6);
+    assert!(generator.maze == reference.maze);
+
+    // another seed or another algorithm starts over
+    assert!(!generator.generate_steps(31, 5));
+    assert!(!generator.generate_steps(30, 5));
+    generator.generate_prim(30);
+    assert!(!generator.generate_steps(30, 5));
+}
+
+#[test]
+fn stats_are_consistent() {
+    let maze = gen_maze_seeded(&size(9, 8), 33);
+    let stats = maze.stats();
+
+    assert_eq!(stats.dead_ends + stats.corridors + stats.junctions, 9 * 8);
+    assert_eq!(stats.passage_count, 9 * 8 - 1);
+    assert_eq!(stats.dead_ends, maze.dead_ends().len());
+    assert!(stats.longest_corridor >= 1 && stats.longest_corridor <= stats.corridors);
+    // every passage adds to the degree of both of its cells
+    let degrees: usize = maze
+        .iter_cells()
+        .map(|cell| maze.passage_degree(cell.coord))
+        .sum();
+    assert_eq!(degrees, 2 * stats.passage_count);
+
+    let snake = serpentine().stats();
+    assert_eq!(
+        snake,
+        MazeStats {
+            dead_ends: 2,
+            corridors: 7,
+            junctions: 0,
+            longest_corridor: 7,
+            passage_count: 8,
+        }
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn seed_zero_gives_the_documented_maze() {
+    let mut generator = MazeGen::new(&size(4, 4));
+    generator.generate_seeded(0);
+
+    let expected = concat!(
+        " _______\n",
+        "|     # |\n",
+        "|#### # |\n",
+        "| #   # |\n",
+        "| # ### |\n",
+        "| # #   |\n",
+        "| # ### |\n",
+        "|       |\n",
+    );
+    assert_eq!(generator.maze.to_string(), expected);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn single_row_and_column_render() {
+    let mut column = Maze::new(&size(1, 3));
+    column.enable_all_walls();
+    column.connect((0, 0), (0, 1)).unwrap();
+    assert_eq!(column.to_string(), " _\n| |\n| |\n| |\n|#|\n| |\n");
+
+    let mut row = Maze::new(&size(3, 1));
+    row.enable_all_walls();
+    row.connect((0, 0), (1, 0)).unwrap();
+    assert_eq!(row.to_string(), " _____\n|   # |\n");
+}
+
+#[test]
+fn render_ansi_colors_walls_and_path() {
+    const WALL: &str = "\x1b[47m  \x1b[0m";
+    const PATH: &str = "\x1b[41m  \x1b[0m";
+    let maze = serpentine();
+
+    let plain = maze.render_ansi(None);
+    assert!(plain.contains(WALL));
+    assert!(!plain.contains("\x1b[41m"));
+    assert_eq!(plain.lines().count(), 7);
+
+    let path = maze.solve_bfs((0, 0), (2, 2)).unwrap();
+    let colored = maze.render_ansi(Some(&path));
+    // the 9 cells and the 8 passages between them
+    assert_eq!(colored.matches(PATH).count(), 17);
+    assert_eq!(colored.matches(WALL).count(), plain.matches(WALL).count());
+}
+
+#[test]
+fn to_csv_has_a_row_per_cell() {
+    let maze = serpentine();
+    let csv = maze.to_csv();
+    let lines: Vec<&str> = csv.lines().collect();
+
+    assert_eq!(lines.len(), 3 * 3 + 1);
+    assert_eq!(lines[0], "x,y,north,east,south,west");
+    // the entrance is open, the corridor leads east
+    assert_eq!(lines[1], "0,0,0,0,1,1");
+    // the exit is open, the corridor comes from the west
+    assert_eq!(lines[9], "2,2,1,1,0,0");
+}
+
+#[test]
+fn masked_halves_are_unreachable() {
+    // the middle column splits the grid in two
+    let mut generator = MazeGen::new_masked(&size(5, 3), &|(i, _)| i != 2);
+    generator.generate_seeded(35);
+    let maze = &generator.maze;
+
+    assert!(maze.is_reachable((0, 0), (1, 2)));
+    assert!(!maze.is_reachable((0, 0), (4, 0)));
+    assert!(!maze.is_reachable((1, 1), (2, 1)));
+}
+
+#[test]
+fn reachable_count_skips_masked_cells() {
+    let mut generator = MazeGen::new_masked(&size(7, 7), &ring_mask);
+    generator.generate_seeded(37);
+
+    assert_eq!(generator.maze.reachable_count((1, 0)), 7 * 7 - 10);
+    assert_eq!(generator.maze.reachable_count((3, 3)), 1);
+    assert_eq!(generator.maze.reachable_count((0, 0)), 1);
+}
+
+#[test]
+fn loop_density_adds_passages() {
+    let mut generator = MazeGen::new(&size(10, 10));
+    generator.generate_with_loops(39, 0.0);
+    assert!(generator.maze.is_perfect());
+    let mut reference = MazeGen::new(&size(10, 10));
+    reference.generate_seeded(39);
+    assert!(generator.maze == reference.maze);
+
+    generator.generate_with_loops(39, 0.9);
+    assert_eq!(generator.maze.reachable_count((0, 0)), 10 * 10);
+    assert!(generator.maze.passages().count() > 10 * 10 - 1);
+    assert!(!generator.maze.is_perfect());
+}
+
+#[test]
+fn isolated_cells_finds_a_sealed_interior_cell() {
+    // a ring around (1, 1)
+    let mut maze = Maze::new(&size(3, 3));
+    maze.enable_all_walls();
+    let ring = [
+        (0, 0),
+        (1, 0),
+        (2, 0),
+        (2, 1),
+        (2, 2),
+        (1, 2),
+        (0, 2),
+        (0, 1),
+    ];
+    for pair in ring.windows(2) {
+        maze.connect(pair[0], pair[1]).unwrap();
+    }
+    assert_eq!(maze.isolated_cells(), vec![(1, 1)]);
+
+    maze.connect((1, 1), (1, 0)).unwrap();
+    assert!(maze.isolated_cells().is_empty());
+}
+
+#[test]
+fn complete_keeps_seams_and_openings() {
+    let mut base = Maze::new_with_topology(&size(6, 4), Topology::Toroidal);
+    base.enable_all_walls();
+    base.disable_wall((5, 2), &TileDirection::EAST);
+    let mut generator = MazeGen::new_with_topology(&size(6, 4), Topology::Toroidal);
+    generator.generate_seeded(41);
+    generator.complete(&base, 41);
+    assert!(!generator.maze.is_wall_enabled((5, 2), &TileDirection::EAST));
+    assert!(!generator.maze.is_wall_enabled((0, 2), &TileDirection::WEST));
+    assert!(generator.maze.is_perfect());
+    // the previous run's visits aren't reported
+    assert!(generator.visit_order().is_empty());
+
+    let mut base = Maze::new(&size(5, 5));
+    base.enable_all_walls();
+    base.set_entrance((3, 0), &TileDirection::NORTH);
+    base.set_exit((0, 3), &TileDirection::WEST);
+    let mut generator = MazeGen::new(&size(5, 5));
+    generator.complete(&base, 43);
+    let maze = &generator.maze;
+    assert_eq!((maze.entrance, maze.exit), ((3, 0), (0, 3)));
+    assert!(!maze.is_wall_enabled((3, 0), &TileDirection::NORTH));
+    assert!(!maze.is_wall_enabled((0, 3), &TileDirection::WEST));
+    assert!(maze.is_wall_enabled((0, 0), &TileDirection::NORTH));
+    assert!(maze.is_wall_enabled((4, 4), &TileDirection::SOUTH));
+    assert!(maze.is_perfect());
+}
+
+#[test]
+fn origin_shift_init_drops_an_unfinished_run() {
+    let mut generator = MazeGen::new(&size(8, 8));
+    assert!(!generator.generate_steps(1, 5));
+    generator.origin_shift_init(2);
+    assert!(generator.maze.is_perfect());
+
+    let shifted = generator.maze.clone();
+    assert!(!generator.step());
+    assert!(generator.maze == shifted);
+    assert!(generator.visit_order().is_empty());
+}